@@ -0,0 +1,45 @@
+use chrono::DateTime;
+use serde::Serialize;
+
+// 版本和构建信息，用于「关于」窗口和问题排查
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct AppInfo {
+    pub version: String,
+    pub tauri_version: String,
+    pub git_hash: String,
+    pub build_time: String,
+    pub os: String,
+    pub arch: String,
+}
+
+pub(crate) fn app_info() -> AppInfo {
+    let build_time = env!("BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| "unknown".to_string());
+    AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        git_hash: env!("GIT_HASH").to_string(),
+        build_time,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    }
+}
+
+// 一行文字的构建信息，写在启动日志开头
+pub(crate) fn summary() -> String {
+    let info = app_info();
+    format!(
+        "v{} ({}, built {}) tauri {} on {}/{}",
+        info.version, info.git_hash, info.build_time, info.tauri_version, info.os, info.arch
+    )
+}
+
+// 获取版本和构建信息
+#[tauri::command]
+pub(crate) fn get_app_info() -> AppInfo {
+    app_info()
+}
@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::{attention, error::AppError, history, log, LogLevel, notify, persist, sound, tick, tick::PriceTick, AppSettings};
+
+const ALERTS_KEY: &str = "alerts";
+const VELOCITY_ALERTS_KEY: &str = "velocity_alerts";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Alert {
+    pub symbol: String,
+    pub above: Option<f64>,
+    pub below: Option<f64>,
+}
+
+// 告警及其触发状态；条件成立时触发一次，条件解除后才会再次触发
+struct AlertEntry {
+    alert: Alert,
+    above_fired: bool,
+    below_fired: bool,
+}
+
+impl AlertEntry {
+    fn new(alert: Alert) -> Self {
+        AlertEntry {
+            alert,
+            above_fired: false,
+            below_fired: false,
+        }
+    }
+}
+
+pub(crate) struct AlertsState(Mutex<Vec<AlertEntry>>);
+
+impl AlertsState {
+    pub(crate) fn new() -> Self {
+        AlertsState(Mutex::new(Vec::new()))
+    }
+}
+
+// 波动告警：window_secs 秒内价格变动超过 delta 时触发
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct VelocityAlert {
+    pub symbol: String,
+    pub delta: f64,
+    pub window_secs: u64,
+}
+
+// 波动告警及上次触发时间；触发后一个窗口内不再重复触发
+struct VelocityEntry {
+    alert: VelocityAlert,
+    last_fired: Option<u64>,
+}
+
+#[derive(Default)]
+pub(crate) struct VelocityAlertsState(Mutex<Vec<VelocityEntry>>);
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct AlertTriggered {
+    pub symbol: String,
+    // 价格告警为 above/below，波动告警为 up/down
+    pub direction: &'static str,
+    pub threshold: f64,
+    pub price: f64,
+    pub timestamp: u64,
+    // 波动告警实际测得的变动（带符号）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_secs: Option<u64>,
+}
+
+// 启动时从存储加载告警
+pub(crate) fn load_alerts<R: Runtime>(app: &AppHandle<R>) {
+    let alerts: Vec<Alert> = match persist::store(app) {
+        Ok(store) => match store.get(ALERTS_KEY) {
+            Some(value) => serde_json::from_value(value).unwrap_or_else(|err| {
+                log(LogLevel::Error, &format!("load_alerts: decode failed: {err}"));
+                Vec::new()
+            }),
+            None => Vec::new(),
+        },
+        Err(err) => {
+            log(LogLevel::Error, &format!("load_alerts: open store failed: {err}"));
+            Vec::new()
+        }
+    };
+    if let Ok(mut guard) = app.state::<AlertsState>().0.lock() {
+        *guard = alerts.into_iter().map(AlertEntry::new).collect();
+    }
+
+    let velocity: Vec<VelocityAlert> = persist::store(app)
+        .ok()
+        .and_then(|store| store.get(VELOCITY_ALERTS_KEY))
+        .map(|value| {
+            serde_json::from_value(value).unwrap_or_else(|err| {
+                log(LogLevel::Error, &format!("load_alerts: decode velocity alerts failed: {err}"));
+                Vec::new()
+            })
+        })
+        .unwrap_or_default();
+    if let Ok(mut guard) = app.state::<VelocityAlertsState>().0.lock() {
+        *guard = velocity
+            .into_iter()
+            .map(|alert| VelocityEntry { alert, last_fired: None })
+            .collect();
+    }
+}
+
+fn persist_alerts<R: Runtime>(app: &AppHandle<R>, alerts: &[Alert]) -> Result<(), AppError> {
+    let store = persist::store(app)?;
+    let value = serde_json::to_value(alerts)?;
+    store.set(ALERTS_KEY, value);
+    persist::schedule_store_save(app);
+    Ok(())
+}
+
+fn persist_velocity_alerts<R: Runtime>(app: &AppHandle<R>, alerts: &[VelocityAlert]) -> Result<(), AppError> {
+    let store = persist::store(app)?;
+    let value = serde_json::to_value(alerts)?;
+    store.set(VELOCITY_ALERTS_KEY, value);
+    persist::schedule_store_save(app);
+    Ok(())
+}
+
+fn velocity_snapshot(entries: &[VelocityEntry]) -> Vec<VelocityAlert> {
+    entries.iter().map(|entry| entry.alert.clone()).collect()
+}
+
+fn snapshot(entries: &[AlertEntry]) -> Vec<Alert> {
+    entries.iter().map(|entry| entry.alert.clone()).collect()
+}
+
+// 设置告警（同一品种只保留一条，重复设置会覆盖）
+#[tauri::command]
+pub(crate) fn set_alert<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<AlertsState>,
+    alert: Alert,
+) -> Result<Vec<Alert>, AppError> {
+    if alert.symbol.trim().is_empty() {
+        return Err(AppError::Validation("Alert symbol must not be empty".to_string()));
+    }
+    if alert.above.is_none() && alert.below.is_none() {
+        return Err(AppError::Validation("Alert needs at least one of above/below".to_string()));
+    }
+    let alerts = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Alerts"))?;
+        guard.retain(|entry| entry.alert.symbol != alert.symbol);
+        guard.push(AlertEntry::new(alert));
+        snapshot(&guard)
+    };
+    persist_alerts(&app, &alerts)?;
+    Ok(alerts)
+}
+
+// 列出告警
+#[tauri::command]
+pub(crate) fn list_alerts(state: State<AlertsState>) -> Result<Vec<Alert>, AppError> {
+    let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Alerts"))?;
+    Ok(snapshot(&guard))
+}
+
+// 删除指定品种的告警
+#[tauri::command]
+pub(crate) fn clear_alert<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<AlertsState>,
+    symbol: String,
+) -> Result<Vec<Alert>, AppError> {
+    let alerts = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Alerts"))?;
+        guard.retain(|entry| entry.alert.symbol != symbol);
+        snapshot(&guard)
+    };
+    persist_alerts(&app, &alerts)?;
+    Ok(alerts)
+}
+
+// 设置波动告警（同一品种只保留一条，重复设置会覆盖）
+#[tauri::command]
+pub(crate) fn set_velocity_alert<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<VelocityAlertsState>,
+    alert: VelocityAlert,
+) -> Result<Vec<VelocityAlert>, AppError> {
+    if alert.symbol.trim().is_empty() {
+        return Err(AppError::Validation("Alert symbol must not be empty".to_string()));
+    }
+    if !alert.delta.is_finite() || alert.delta <= 0.0 {
+        return Err(AppError::Validation("Velocity alert delta must be positive".to_string()));
+    }
+    if alert.window_secs == 0 {
+        return Err(AppError::Validation("Velocity alert window must be at least 1 second".to_string()));
+    }
+    let alerts = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Velocity alerts"))?;
+        guard.retain(|entry| entry.alert.symbol != alert.symbol);
+        guard.push(VelocityEntry { alert, last_fired: None });
+        velocity_snapshot(&guard)
+    };
+    persist_velocity_alerts(&app, &alerts)?;
+    Ok(alerts)
+}
+
+// 列出波动告警
+#[tauri::command]
+pub(crate) fn list_velocity_alerts(state: State<VelocityAlertsState>) -> Result<Vec<VelocityAlert>, AppError> {
+    let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Velocity alerts"))?;
+    Ok(velocity_snapshot(&guard))
+}
+
+// 删除指定品种的波动告警
+#[tauri::command]
+pub(crate) fn clear_velocity_alert<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<VelocityAlertsState>,
+    symbol: String,
+) -> Result<Vec<VelocityAlert>, AppError> {
+    let alerts = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Velocity alerts"))?;
+        guard.retain(|entry| entry.alert.symbol != symbol);
+        velocity_snapshot(&guard)
+    };
+    persist_velocity_alerts(&app, &alerts)?;
+    Ok(alerts)
+}
+
+// 用最近行情缓冲检查波动告警：取窗口内相对当前价偏离最大的一侧作为变动
+fn check_velocity<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick, triggered: &mut Vec<AlertTriggered>) {
+    let state = app.state::<VelocityAlertsState>();
+    let Ok(mut guard) = state.0.lock() else {
+        return;
+    };
+    for entry in guard.iter_mut().filter(|entry| entry.alert.symbol == tick.symbol) {
+        let window_ms = entry.alert.window_secs.saturating_mul(1000);
+        if entry
+            .last_fired
+            .is_some_and(|fired| tick.timestamp.saturating_sub(fired) < window_ms)
+        {
+            continue;
+        }
+        let since = tick.timestamp.saturating_sub(window_ms);
+        let Some((low, high)) = tick::bid_range_since(app, &tick.symbol, since) else {
+            continue;
+        };
+        let rise = tick.bid - low;
+        let fall = tick.bid - high;
+        let delta = if rise >= -fall { rise } else { fall };
+        if delta.abs() < entry.alert.delta {
+            continue;
+        }
+        entry.last_fired = Some(tick.timestamp);
+        triggered.push(AlertTriggered {
+            symbol: tick.symbol.clone(),
+            direction: if delta > 0.0 { "up" } else { "down" },
+            threshold: entry.alert.delta,
+            price: tick.bid,
+            timestamp: tick.timestamp,
+            delta: Some(delta),
+            window_secs: Some(entry.alert.window_secs),
+        });
+    }
+}
+
+// 告警通知的正文
+fn describe(event: &AlertTriggered) -> String {
+    match (event.delta, event.window_secs) {
+        (Some(delta), Some(window_secs)) => {
+            let direction = if delta > 0.0 { "上涨" } else { "下跌" };
+            format!(
+                "{} {}秒内{} {:.2}，当前价格 {}",
+                event.symbol, window_secs, direction, delta.abs(), event.price
+            )
+        }
+        _ => {
+            let direction = if event.direction == "above" { "高于" } else { "低于" };
+            format!(
+                "{} 当前价格 {}，已{} {}",
+                event.symbol, event.price, direction, event.threshold
+            )
+        }
+    }
+}
+
+// 用最新行情检查告警，触发的告警通过 alert-triggered 事件通知前端
+pub(crate) fn check_tick<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    let mut triggered = Vec::new();
+    if let Ok(mut guard) = app.state::<AlertsState>().0.lock() {
+        for entry in guard.iter_mut().filter(|entry| entry.alert.symbol == tick.symbol) {
+            if let Some(threshold) = entry.alert.above {
+                let hit = tick.bid >= threshold;
+                if hit && !entry.above_fired {
+                    triggered.push(AlertTriggered {
+                        symbol: tick.symbol.clone(),
+                        direction: "above",
+                        threshold,
+                        price: tick.bid,
+                        timestamp: tick.timestamp,
+                        delta: None,
+                        window_secs: None,
+                    });
+                }
+                entry.above_fired = hit;
+            }
+            if let Some(threshold) = entry.alert.below {
+                let hit = tick.bid <= threshold;
+                if hit && !entry.below_fired {
+                    triggered.push(AlertTriggered {
+                        symbol: tick.symbol.clone(),
+                        direction: "below",
+                        threshold,
+                        price: tick.bid,
+                        timestamp: tick.timestamp,
+                        delta: None,
+                        window_secs: None,
+                    });
+                }
+                entry.below_fired = hit;
+            }
+        }
+    }
+    check_velocity(app, tick, &mut triggered);
+    if triggered.is_empty() {
+        return;
+    }
+    let notifications_enabled = app.state::<AppSettings>().0.read().notifications_enabled;
+    for event in triggered {
+        log(LogLevel::Info, &format!(
+            "alert: {} {} {} at {}{}",
+            event.symbol,
+            event.direction,
+            event.threshold,
+            event.price,
+            event.delta.map(|delta| format!(" (moved {delta:.2})")).unwrap_or_default()
+        ));
+        if notifications_enabled {
+            notify(app, "黄金价格提醒", &describe(&event));
+        }
+        history::record_alert(app, &event);
+        let _ = app.emit("alert-triggered", event);
+    }
+    attention::on_alert(app);
+    sound::on_alert(app);
+}
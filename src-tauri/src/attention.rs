@@ -0,0 +1,50 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, UserAttentionType};
+
+use crate::{error::AppError, log, AppSettings, LogLevel};
+
+// 为提醒临时显示的窗口在多久后重新隐藏（期间用户切到窗口则保留）
+const REVEAL_DURATION_SECS: u64 = 5;
+
+// 请求用户注意：Windows 上闪烁任务栏按钮，macOS 上弹跳程序坞图标；
+// reveal 为真且窗口隐藏时临时显示并聚焦窗口
+pub(crate) fn request_attention<R: Runtime>(app: &AppHandle<R>, reveal: bool) -> Result<(), AppError> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    if reveal && !window.is_visible().unwrap_or(true) {
+        window.show()?;
+        window.set_focus()?;
+        let window = window.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(REVEAL_DURATION_SECS)).await;
+            if !window.is_focused().unwrap_or(false) {
+                let _ = window.hide();
+            }
+        });
+    }
+    window.request_user_attention(Some(UserAttentionType::Critical))?;
+    Ok(())
+}
+
+// 告警触发时按设置请求用户注意
+pub(crate) fn on_alert<R: Runtime>(app: &AppHandle<R>) {
+    let (enabled, reveal) = {
+        let state = app.state::<AppSettings>();
+        let settings = state.0.read();
+        (settings.flash_on_alert, settings.flash_reveal_window)
+    };
+    if !enabled {
+        return;
+    }
+    if let Err(e) = request_attention(app, reveal) {
+        log(LogLevel::Warn, &format!("attention: {}", e));
+    }
+}
+
+// 闪烁任务栏/程序坞提醒用户，show 缺省时按 flash_reveal_window 设置决定是否临时显示窗口
+#[tauri::command]
+pub(crate) fn flash_window<R: Runtime>(app: AppHandle<R>, show: Option<bool>) -> Result<(), AppError> {
+    let reveal = show.unwrap_or_else(|| app.state::<AppSettings>().0.read().flash_reveal_window);
+    request_attention(&app, reveal)
+}
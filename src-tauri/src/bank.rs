@@ -0,0 +1,139 @@
+use futures_util::future::join_all;
+use serde_json::Value;
+use std::{collections::HashMap, time::Duration};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::time::Instant;
+
+use crate::{error::AppError, handle_tick, http_client, log, tick, tick::PriceTick, AppSettings, LogLevel};
+
+// 默认轮询间隔，与原来前端的 3 秒一致
+pub(crate) const DEFAULT_POLL_INTERVAL_SECS: u64 = 3;
+const POLL_CHECK_INTERVAL_MS: u64 = 500;
+const POLL_TIMEOUT_MS: u64 = 5000;
+
+// 银行报价接口：平台 id、行情代码、地址、请求体（有请求体时用 POST）和响应解析函数
+pub(crate) struct BankSource {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub symbol: &'static str,
+    pub url: &'static str,
+    pub body: Option<&'static str>,
+    pub parse: fn(&Value) -> Option<f64>,
+}
+
+pub(crate) const BANK_SOURCES: &[BankSource] = &[
+    BankSource {
+        id: "ms",
+        label: "民生",
+        symbol: "JD-MS",
+        url: "https://api.jdjygold.com/gw/generic/hj/h5/m/latestPrice",
+        body: None,
+        parse: parse_datas_price,
+    },
+    BankSource {
+        id: "gh",
+        label: "工行",
+        symbol: "JD-GH",
+        url: "https://api.jdjygold.com/gw2/generic/jrm/h5/m/icbcLatestPrice?productSku=2005453243",
+        body: Some(r#"{"reqData":{"productSku":"2005453243"}}"#),
+        parse: parse_datas_price,
+    },
+    BankSource {
+        id: "zs",
+        label: "浙商",
+        symbol: "JD-ZS",
+        url: "https://api.jdjygold.com/gw2/generic/jrm/h5/m/stdLatestPrice?productSku=1961543816",
+        body: Some(r#"{"reqData":{"productSku":"1961543816"}}"#),
+        parse: parse_datas_price,
+    },
+];
+
+pub(crate) fn find_source(id: &str) -> Option<&'static BankSource> {
+    BANK_SOURCES.iter().find(|source| source.id == id)
+}
+
+// 价格可能是数字也可能是字符串
+fn as_price(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|price: &f64| price.is_finite() && *price > 0.0)
+}
+
+// {"resultData":{"datas":{"price":..}}}
+fn parse_datas_price(payload: &Value) -> Option<f64> {
+    as_price(payload.pointer("/resultData/datas/price")?)
+}
+
+async fn fetch_price(client: &reqwest::Client, source: &BankSource) -> Result<f64, AppError> {
+    let request = match source.body {
+        Some(body) => client
+            .post(source.url)
+            .header("Content-Type", "application/json")
+            .body(body),
+        None => client.get(source.url),
+    };
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!("status {}", response.status())));
+    }
+    let payload: Value = response.json().await?;
+    (source.parse)(&payload).ok_or_else(|| AppError::Network("no price in response".to_string()))
+}
+
+// 后台轮询已启用平台的银行报价，按各平台的间隔请求，结果和 WebSocket 行情走同一处理流程
+pub(crate) fn spawn_bank_poller<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_polled: HashMap<&'static str, Instant> = HashMap::new();
+        let mut interval = tokio::time::interval(Duration::from_millis(POLL_CHECK_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            let due: Vec<&'static BankSource> = {
+                let state = app.state::<AppSettings>();
+                let settings = state.0.read();
+                settings
+                    .platforms
+                    .iter()
+                    .filter(|platform| platform.enabled)
+                    .filter_map(|platform| {
+                        let source = find_source(&platform.id)?;
+                        let period = Duration::from_secs(platform.poll_interval_secs?.max(1));
+                        let due = last_polled.get(source.id).is_none_or(|last| last.elapsed() >= period);
+                        due.then_some(source)
+                    })
+                    .collect()
+            };
+            if due.is_empty() {
+                continue;
+            }
+            let client = match http_client(&app, POLL_TIMEOUT_MS) {
+                Ok(client) => client,
+                Err(e) => {
+                    log(LogLevel::Error, &format!("bank_poller: {}", e));
+                    continue;
+                }
+            };
+            let now = Instant::now();
+            for source in &due {
+                last_polled.insert(source.id, now);
+            }
+            let results = join_all(due.iter().map(|source| fetch_price(&client, source))).await;
+            for (source, result) in due.into_iter().zip(results) {
+                match result {
+                    Ok(price) => handle_tick(
+                        &app,
+                        PriceTick {
+                            symbol: source.symbol.to_string(),
+                            bid: price,
+                            ask: price,
+                            timestamp: tick::now_millis(),
+                        },
+                    ),
+                    Err(e) => log(LogLevel::Warn, &format!("bank_poller: {} failed: {}", source.id, e)),
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,103 @@
+use base64::Engine;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, log, LogLevel};
+
+// 截取主窗口内容为 PNG；传入 path 时写入文件并返回路径，否则返回 base64 编码的 PNG
+#[tauri::command]
+pub(crate) fn capture_widget<R: Runtime>(app: AppHandle<R>, path: Option<String>) -> Result<String, AppError> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    let png = capture_png(&window)?;
+    log(LogLevel::Info, &format!("capture_widget: captured {} bytes", png.len()));
+    match path {
+        Some(path) => {
+            std::fs::write(&path, &png)?;
+            Ok(path)
+        }
+        None => Ok(base64::engine::general_purpose::STANDARD.encode(png)),
+    }
+}
+
+// 用 PrintWindow 把窗口客户区画到内存位图，再编码为 PNG（窗口隐藏或被遮挡时也能截取）
+#[cfg(windows)]
+fn capture_png<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Result<Vec<u8>, AppError> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits, ReleaseDC,
+        SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS, PW_CLIENTONLY};
+    use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    // PW_RENDERFULLCONTENT：WebView2 用 DirectComposition 绘制，不加这个标志截到的是空白
+    const PW_RENDERFULLCONTENT: u32 = 2;
+
+    let hwnd = window.hwnd().map_err(|e| AppError::Platform(e.to_string()))?;
+    let mut rect = RECT::default();
+    unsafe { GetClientRect(hwnd, &mut rect) }.map_err(|e| AppError::Platform(e.to_string()))?;
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    if width <= 0 || height <= 0 {
+        return Err(AppError::Platform("Window has no visible area".to_string()));
+    }
+
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    let copied = unsafe {
+        let window_dc = GetDC(Some(hwnd));
+        let memory_dc = CreateCompatibleDC(Some(window_dc));
+        let bitmap = CreateCompatibleBitmap(window_dc, width, height);
+        let previous = SelectObject(memory_dc, bitmap.into());
+        let printed = PrintWindow(hwnd, memory_dc, PRINT_WINDOW_FLAGS(PW_CLIENTONLY.0 | PW_RENDERFULLCONTENT)).as_bool();
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // 负高度表示自上而下的行顺序
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let lines = GetDIBits(
+            memory_dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(pixels.as_mut_ptr().cast()),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+        SelectObject(memory_dc, previous);
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory_dc);
+        ReleaseDC(Some(hwnd), window_dc);
+        printed && lines == height
+    };
+    if !copied {
+        return Err(AppError::Platform("PrintWindow failed to capture the window".to_string()));
+    }
+
+    // GDI 位图为 BGRA 且 alpha 不可信，转为不透明的 RGBA
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        pixel[3] = 255;
+    }
+    let mut png = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| AppError::Internal(e.to_string()))?;
+        writer.write_image_data(&pixels).map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+    Ok(png)
+}
+
+#[cfg(not(windows))]
+fn capture_png<R: Runtime>(_window: &tauri::WebviewWindow<R>) -> Result<Vec<u8>, AppError> {
+    Err(AppError::Platform("Widget capture is not supported on this platform".to_string()))
+}
@@ -0,0 +1,29 @@
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::{currency, error::AppError, log, tick, AppSettings, LogLevel, XAU_SYMBOL};
+
+// 把某品种缓存中的最新买价按显示单位和精度格式化后写入剪贴板，返回写入的文本
+pub(crate) fn copy_latest_price<R: Runtime>(app: &AppHandle<R>, symbol: &str) -> Result<String, AppError> {
+    let latest = tick::latest_tick(app, symbol)
+        .ok_or_else(|| AppError::NotFound(format!("No current price for {symbol}")))?;
+    let text = currency::format_tick(&app.state::<AppSettings>().0.read(), &latest, None).bid_text;
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| AppError::Platform(e.to_string()))?;
+    log(LogLevel::Info, &format!("clipboard: copied {symbol} {text}"));
+    Ok(text)
+}
+
+// 托盘和快捷键用：复制国际金价，失败只记录日志
+pub(crate) fn copy_xau_price<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = copy_latest_price(app, XAU_SYMBOL) {
+        log(LogLevel::Warn, &format!("clipboard: {}", e));
+    }
+}
+
+// 复制某品种当前价格到剪贴板，没有行情时返回 NotFound
+#[tauri::command]
+pub(crate) fn copy_price<R: Runtime>(app: AppHandle<R>, symbol: String) -> Result<String, AppError> {
+    copy_latest_price(&app, &symbol)
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::{error::AppError, log, persist, refresh_tray_menu, LogLevel};
+
+const COLOR_PRESETS_KEY: &str = "color_presets";
+
+// 默认文字颜色（金色，在深色背景上对比度高）
+pub(crate) const DEFAULT_TEXT_COLOR: &str = "#ffd700";
+pub(crate) const TEXT_COLOR_PRESETS: &[(&str, &str)] = &[("金色", "#ffd700"), ("白色", "#ffffff"), ("青色", "#00e5ff")];
+
+// 主题：一次设置背景色、文字颜色和透明度
+#[derive(Serialize, Debug, Clone, Copy)]
+pub(crate) struct Theme {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub bg_color: &'static str,
+    pub text_color: &'static str,
+    pub opacity: f64,
+}
+
+pub(crate) const THEMES: &[Theme] = &[
+    Theme { name: "Dark", label: "深色", bg_color: "#2c3e50", text_color: "#ffd700", opacity: 1.0 },
+    Theme { name: "Light", label: "浅色", bg_color: "#f5f5f5", text_color: "#333333", opacity: 1.0 },
+    Theme { name: "High Contrast", label: "高对比度", bg_color: "#000000", text_color: "#ffffff", opacity: 1.0 },
+    Theme { name: "Terminal Green", label: "终端绿", bg_color: "#0c0c0c", text_color: "#33ff33", opacity: 0.9 },
+];
+
+pub(crate) fn find_theme(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|theme| theme.name.eq_ignore_ascii_case(name))
+}
+
+// 列出内置主题
+#[tauri::command]
+pub(crate) fn list_themes() -> Vec<Theme> {
+    THEMES.to_vec()
+}
+
+// 内置颜色，不可删除
+const BUILTIN_COLOR_PRESETS: &[(&str, &str)] = &[("深色", "#2c3e50"), ("蓝色", "#1e3a5f"), ("黑色", "#000000")];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ColorPreset {
+    pub name: String,
+    pub hex: String,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+const COLOR_FORMATS: &str = "expected #RGB, #RRGGBB, #RRGGBBAA, rgb(r, g, b) or rgba(r, g, b, a)";
+
+// 解析颜色并统一为小写 #rrggbb（不透明）或 #rrggbbaa
+pub(crate) fn parse_color(input: &str) -> Result<String, AppError> {
+    let color = input.trim().to_ascii_lowercase();
+    let invalid = || AppError::Validation(format!("Invalid color '{input}': {COLOR_FORMATS}"));
+    let (r, g, b, a) = if let Some(hex) = color.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channel = |i: usize, width: usize| {
+            let value = u8::from_str_radix(&hex[i * width..(i + 1) * width], 16).unwrap_or(0);
+            if width == 1 { value * 17 } else { value }
+        };
+        match hex.len() {
+            3 => (channel(0, 1), channel(1, 1), channel(2, 1), 255),
+            6 => (channel(0, 2), channel(1, 2), channel(2, 2), 255),
+            8 => (channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2)),
+            _ => return Err(invalid()),
+        }
+    } else {
+        let (args, with_alpha) = if let Some(rest) = color.strip_prefix("rgba(") {
+            (rest, true)
+        } else if let Some(rest) = color.strip_prefix("rgb(") {
+            (rest, false)
+        } else {
+            return Err(invalid());
+        };
+        let args = args.strip_suffix(')').ok_or_else(invalid)?;
+        let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        if parts.len() != if with_alpha { 4 } else { 3 } {
+            return Err(invalid());
+        }
+        let channel = |part: &str| part.parse::<u8>().map_err(|_| invalid());
+        let alpha = if with_alpha {
+            let alpha = parts[3].parse::<f64>().map_err(|_| invalid())?;
+            if !(0.0..=1.0).contains(&alpha) {
+                return Err(invalid());
+            }
+            (alpha * 255.0).round() as u8
+        } else {
+            255
+        };
+        (channel(parts[0])?, channel(parts[1])?, channel(parts[2])?, alpha)
+    };
+    if a == 255 {
+        Ok(format!("#{r:02x}{g:02x}{b:02x}"))
+    } else {
+        Ok(format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+    }
+}
+
+// 用户保存的颜色
+pub(crate) struct ColorPresets(Mutex<Vec<ColorPreset>>);
+
+impl ColorPresets {
+    pub(crate) fn new() -> Self {
+        ColorPresets(Mutex::new(Vec::new()))
+    }
+}
+
+// 启动时从存储加载用户颜色
+pub(crate) fn load_color_presets<R: Runtime>(app: &AppHandle<R>) {
+    let presets: Vec<ColorPreset> = match persist::store(app) {
+        Ok(store) => match store.get(COLOR_PRESETS_KEY) {
+            Some(value) => serde_json::from_value(value).unwrap_or_else(|err| {
+                log(LogLevel::Error, &format!("load_color_presets: decode failed: {err}"));
+                Vec::new()
+            }),
+            None => Vec::new(),
+        },
+        Err(err) => {
+            log(LogLevel::Error, &format!("load_color_presets: open store failed: {err}"));
+            Vec::new()
+        }
+    };
+    if let Ok(mut guard) = app.state::<ColorPresets>().0.lock() {
+        *guard = presets;
+    }
+}
+
+fn persist_color_presets<R: Runtime>(app: &AppHandle<R>, presets: &[ColorPreset]) -> Result<(), AppError> {
+    let store = persist::store(app)?;
+    let value = serde_json::to_value(presets)?;
+    store.set(COLOR_PRESETS_KEY, value);
+    persist::schedule_store_save(app);
+    Ok(())
+}
+
+// 内置颜色在前，用户颜色在后
+pub(crate) fn all_color_presets<R: Runtime>(app: &AppHandle<R>) -> Vec<ColorPreset> {
+    let mut presets: Vec<ColorPreset> = BUILTIN_COLOR_PRESETS
+        .iter()
+        .map(|(name, hex)| ColorPreset {
+            name: name.to_string(),
+            hex: hex.to_string(),
+            builtin: true,
+        })
+        .collect();
+    if let Ok(guard) = app.state::<ColorPresets>().0.lock() {
+        presets.extend(guard.iter().cloned());
+    }
+    presets
+}
+
+// 列出全部颜色
+#[tauri::command]
+pub(crate) fn list_color_presets<R: Runtime>(app: AppHandle<R>) -> Vec<ColorPreset> {
+    all_color_presets(&app)
+}
+
+// 保存用户颜色（同名覆盖）
+#[tauri::command]
+pub(crate) fn add_color_preset<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<ColorPresets>,
+    name: String,
+    hex: String,
+) -> Result<Vec<ColorPreset>, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Color preset name must not be empty".to_string()));
+    }
+    if BUILTIN_COLOR_PRESETS.iter().any(|(builtin, _)| *builtin == name) {
+        return Err(AppError::Validation(format!("Color preset '{name}' is built in")));
+    }
+    let hex = parse_color(&hex)?;
+    let presets = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Color presets"))?;
+        guard.retain(|preset| preset.name != name);
+        guard.push(ColorPreset {
+            name,
+            hex,
+            builtin: false,
+        });
+        guard.clone()
+    };
+    persist_color_presets(&app, &presets)?;
+    refresh_tray_menu(&app);
+    Ok(all_color_presets(&app))
+}
+
+// 删除用户颜色
+#[tauri::command]
+pub(crate) fn remove_color_preset<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<ColorPresets>,
+    name: String,
+) -> Result<Vec<ColorPreset>, AppError> {
+    if BUILTIN_COLOR_PRESETS.iter().any(|(builtin, _)| *builtin == name) {
+        return Err(AppError::Validation(format!("Color preset '{name}' is built in")));
+    }
+    let presets = {
+        let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Color presets"))?;
+        guard.retain(|preset| preset.name != name);
+        guard.clone()
+    };
+    persist_color_presets(&app, &presets)?;
+    refresh_tray_menu(&app);
+    Ok(all_color_presets(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#FA0").unwrap(), "#ffaa00");
+        assert_eq!(parse_color(" #2C3E50 ").unwrap(), "#2c3e50");
+        assert_eq!(parse_color("#2c3e5080").unwrap(), "#2c3e5080");
+        // 完全不透明的 #RRGGBBAA 统一为 #rrggbb
+        assert_eq!(parse_color("#2c3e50ff").unwrap(), "#2c3e50");
+    }
+
+    #[test]
+    fn parses_rgb_functions() {
+        assert_eq!(parse_color("rgb(44, 62, 80)").unwrap(), "#2c3e50");
+        assert_eq!(parse_color("RGBA(255,0,0,0.5)").unwrap(), "#ff000080");
+        assert_eq!(parse_color("rgba(0, 0, 0, 1)").unwrap(), "#000000");
+        assert_eq!(parse_color("rgba(0, 0, 0, 0)").unwrap(), "#00000000");
+    }
+
+    #[test]
+    fn rejects_alpha_out_of_range() {
+        for input in ["rgba(0, 0, 0, 1.5)", "rgba(0, 0, 0, -0.1)", "rgba(0, 0, 0, NaN)"] {
+            assert!(matches!(parse_color(input), Err(AppError::Validation(_))), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for input in [
+            "",
+            "red",
+            "#",
+            "#12",
+            "#1234",
+            "#12345g",
+            "#123456789",
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4)",
+            "rgba(1, 2, 3)",
+            "rgb(1, 2, 3",
+            "rgb(-1, 2, 3)",
+            "#ｆｆｆ",
+        ] {
+            assert!(matches!(parse_color(input), Err(AppError::Validation(_))), "{input}");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Notify;
+
+use crate::{error::AppError, http_client, log, tick::PriceTick, update_settings, AppSettings, LogLevel, Settings, XAU_SYMBOL};
+
+// 1 金衡盎司对应的克数
+const GRAMS_PER_TROY_OUNCE: f64 = 31.1035;
+pub(crate) const DEFAULT_USD_CNY_RATE: f64 = 7.2;
+
+pub(crate) const DEFAULT_FX_ENDPOINT: &str = "https://open.er-api.com/v6/latest/USD";
+pub(crate) const DEFAULT_FX_POLL_INTERVAL_MINS: u64 = 60;
+// 轮询关闭时隔多久再检查一次设置
+const FX_DISABLED_RECHECK_SECS: u64 = 60;
+const FX_FETCH_TIMEOUT_MS: u64 = 10000;
+
+// 各品种默认显示的小数位数，未列出的品种用 FALLBACK_PRECISION
+const DEFAULT_PRECISION: &[(&str, u8)] = &[("WG-XAUUSD", 2)];
+const FALLBACK_PRECISION: u8 = 2;
+pub(crate) const MAX_PRECISION: u8 = 6;
+
+// 唤醒汇率轮询任务，立即拉取一次
+#[derive(Default)]
+pub(crate) struct FxRefresh(Notify);
+
+pub(crate) fn request_fx_refresh<R: Runtime>(app: &AppHandle<R>) {
+    app.state::<FxRefresh>().0.notify_one();
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct FxRateUpdated {
+    pub rate: f64,
+    pub timestamp: u64,
+}
+
+// 前端显示单位：原始报价或折算为人民币/克
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DisplayUnit {
+    #[default]
+    Raw,
+    CnyPerGram,
+}
+
+pub(crate) fn validate_rate(rate: f64) -> Result<(), AppError> {
+    if rate.is_finite() && rate > 0.0 {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("Invalid USD/CNY rate {rate}: must be a positive number")))
+    }
+}
+
+// 美元/盎司 折算为 人民币/克
+pub(crate) fn convert_xau(usd_per_oz: f64, usd_cny_rate: f64) -> f64 {
+    usd_per_oz * usd_cny_rate / GRAMS_PER_TROY_OUNCE
+}
+
+fn is_xau(symbol: &str) -> bool {
+    symbol == XAU_SYMBOL
+}
+
+// 按显示单位换算要发给前端的行情，只有 XAU 需要换算
+pub(crate) fn to_display_units(settings: &Settings, tick: &PriceTick) -> PriceTick {
+    let mut display = tick.clone();
+    display.bid = to_display_price(settings, &tick.symbol, tick.bid);
+    display.ask = to_display_price(settings, &tick.symbol, tick.ask);
+    display
+}
+
+pub(crate) fn to_display_price(settings: &Settings, symbol: &str, price: f64) -> f64 {
+    if settings.display_unit == DisplayUnit::CnyPerGram && is_xau(symbol) {
+        convert_xau(price, settings.usd_cny_rate)
+    } else {
+        price
+    }
+}
+
+pub(crate) fn default_precision() -> HashMap<String, u8> {
+    DEFAULT_PRECISION
+        .iter()
+        .map(|(symbol, digits)| (symbol.to_string(), *digits))
+        .collect()
+}
+
+fn precision_for(settings: &Settings, symbol: &str) -> u8 {
+    settings
+        .precision
+        .get(symbol)
+        .copied()
+        .unwrap_or(FALLBACK_PRECISION)
+        .min(MAX_PRECISION)
+}
+
+// 推送给前端的行情：原始数值之外附带按品种精度格式化的文本
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct DisplayTick {
+    #[serde(flatten)]
+    pub tick: PriceTick,
+    pub precision: u8,
+    pub bid_text: String,
+    pub ask_text: String,
+    // 开启 ema_period 时的平滑价格（显示单位）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ema: Option<f64>,
+}
+
+// 换算显示单位并按精度格式化
+pub(crate) fn format_tick(settings: &Settings, tick: &PriceTick, ema: Option<f64>) -> DisplayTick {
+    let ema = ema.map(|ema| to_display_price(settings, &tick.symbol, ema));
+    let tick = to_display_units(settings, tick);
+    let precision = precision_for(settings, &tick.symbol);
+    let digits = precision as usize;
+    DisplayTick {
+        precision,
+        bid_text: format!("{:.digits$}", tick.bid),
+        ask_text: format!("{:.digits$}", tick.ask),
+        ema,
+        tick,
+    }
+}
+
+// 后台定时拉取汇率，失败时保留原汇率；汇率随设置一起保存，离线冷启动时沿用上次的值
+pub(crate) fn spawn_fx_poller<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (endpoint, interval_mins) = {
+                let state = app.state::<AppSettings>();
+                let guard = state.0.read();
+                (guard.fx_endpoint.clone(), guard.fx_poll_interval_mins)
+            };
+            let wait = if interval_mins == 0 || endpoint.trim().is_empty() {
+                Duration::from_secs(FX_DISABLED_RECHECK_SECS)
+            } else {
+                match fetch_rate(&app, &endpoint).await {
+                    Ok(rate) => apply_rate(&app, rate),
+                    Err(e) => log(LogLevel::Error, &format!("fx_poller: {} (keeping previous rate)", e)),
+                }
+                Duration::from_secs(interval_mins * 60)
+            };
+            let refresh = app.state::<FxRefresh>();
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = refresh.0.notified() => {}
+            }
+        }
+    });
+}
+
+async fn fetch_rate<R: Runtime>(app: &AppHandle<R>, endpoint: &str) -> Result<f64, AppError> {
+    let client = http_client(app, FX_FETCH_TIMEOUT_MS)?;
+    let response = client.get(endpoint).send().await?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!("status {}", response.status())));
+    }
+    let payload: serde_json::Value = response.json().await?;
+    let rate = parse_rate(&payload).ok_or_else(|| AppError::Network("no CNY rate in response".to_string()))?;
+    validate_rate(rate)?;
+    Ok(rate)
+}
+
+// 兼容 {"rates":{"CNY":..}}、{"CNY":..} 和 {"rate":..} 几种常见格式
+fn parse_rate(payload: &serde_json::Value) -> Option<f64> {
+    let rates = payload.get("rates").unwrap_or(payload);
+    let value = rates.get("CNY").or_else(|| payload.get("rate"))?;
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn apply_rate<R: Runtime>(app: &AppHandle<R>, rate: f64) {
+    let changed = app.state::<AppSettings>().0.read().usd_cny_rate != rate;
+    if changed {
+        let saved = update_settings(app, |current| {
+            current.usd_cny_rate = rate;
+            Ok(())
+        });
+        if let Err(e) = saved {
+            log(LogLevel::Error, &format!("fx_poller: save rate failed: {}", e));
+            return;
+        }
+    }
+    log(LogLevel::Info, &format!("fx_poller: USD/CNY {}", rate));
+    let _ = app.emit(
+        "fx-rate-updated",
+        FxRateUpdated {
+            rate,
+            timestamp: crate::tick::now_millis(),
+        },
+    );
+}
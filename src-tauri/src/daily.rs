@@ -0,0 +1,142 @@
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Mutex};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{currency, error::AppError, log, persist, tick::PriceTick, AppSettings, LogLevel};
+
+const DAILY_STATS_KEY: &str = "daily_stats";
+
+// 某品种当天（本地时区）的开盘、最高、最低、最新价和涨跌
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct DailyStats {
+    pub symbol: String,
+    pub date: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub last: f64,
+    pub change: f64,
+    pub change_pct: f64,
+}
+
+#[derive(Default)]
+pub(crate) struct DailyStatsState(Mutex<HashMap<String, DailyStats>>);
+
+fn local_date(timestamp_ms: u64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms as i64)
+        .single()
+        .unwrap_or_else(Local::now)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+// 启动时恢复当天的统计，跨天的记录丢弃
+pub(crate) fn load_daily_stats<R: Runtime>(app: &AppHandle<R>) {
+    let stats: Vec<DailyStats> = match persist::store(app) {
+        Ok(store) => match store.get(DAILY_STATS_KEY) {
+            Some(value) => serde_json::from_value(value).unwrap_or_else(|err| {
+                log(LogLevel::Error, &format!("load_daily_stats: decode failed: {err}"));
+                Vec::new()
+            }),
+            None => Vec::new(),
+        },
+        Err(err) => {
+            log(LogLevel::Error, &format!("load_daily_stats: open store failed: {err}"));
+            Vec::new()
+        }
+    };
+    let today = today();
+    if let Ok(mut guard) = app.state::<DailyStatsState>().0.lock() {
+        *guard = stats
+            .into_iter()
+            .filter(|stats| stats.date == today)
+            .map(|stats| (stats.symbol.clone(), stats))
+            .collect();
+    }
+}
+
+// 用最新行情更新当天统计，过了本地零点重新开始，并推送 daily-stats
+pub(crate) fn record<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    let date = local_date(tick.timestamp);
+    let price = tick.bid;
+    let (updated, all) = {
+        let state = app.state::<DailyStatsState>();
+        let Ok(mut guard) = state.0.lock() else {
+            return;
+        };
+        let entry = guard.entry(tick.symbol.clone()).or_insert_with(|| DailyStats {
+            symbol: tick.symbol.clone(),
+            date: date.clone(),
+            open: price,
+            high: price,
+            low: price,
+            last: price,
+            change: 0.0,
+            change_pct: 0.0,
+        });
+        if entry.date != date {
+            log(LogLevel::Info, &format!("daily_stats: new day {} for {}", date, tick.symbol));
+            entry.date = date;
+            entry.open = price;
+            entry.high = price;
+            entry.low = price;
+        }
+        entry.high = entry.high.max(price);
+        entry.low = entry.low.min(price);
+        entry.last = price;
+        entry.change = price - entry.open;
+        entry.change_pct = if entry.open != 0.0 { entry.change / entry.open * 100.0 } else { 0.0 };
+        (entry.clone(), guard.values().cloned().collect::<Vec<_>>())
+    };
+
+    // 只更新内存中的 store，随下一次写盘（或退出时）一起保存
+    match (persist::store(app), serde_json::to_value(&all)) {
+        (Ok(store), Ok(value)) => {
+            store.set(DAILY_STATS_KEY, value);
+            persist::mark_dirty(app);
+        }
+        (Err(err), _) => log(LogLevel::Error, &format!("daily_stats: open store failed: {err}")),
+        (_, Err(err)) => log(LogLevel::Error, &format!("daily_stats: encode failed: {err}")),
+    }
+    let _ = app.emit("daily-stats", to_display(app, updated));
+}
+
+// 按显示单位换算价格（涨跌幅不变）
+fn to_display<R: Runtime>(app: &AppHandle<R>, stats: DailyStats) -> DailyStats {
+    let settings = app.state::<AppSettings>();
+    let settings = settings.0.read();
+    let symbol = stats.symbol.clone();
+    let convert = |price: f64| currency::to_display_price(&settings, &symbol, price);
+    DailyStats {
+        open: convert(stats.open),
+        high: convert(stats.high),
+        low: convert(stats.low),
+        last: convert(stats.last),
+        change: convert(stats.change),
+        ..stats
+    }
+}
+
+// 获取某品种当天的统计；今天还没有行情时返回 None
+#[tauri::command]
+pub(crate) fn get_daily_stats<R: Runtime>(app: AppHandle<R>, symbol: String) -> Result<Option<DailyStats>, AppError> {
+    let stats = {
+        let state = app.state::<DailyStatsState>();
+        let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Daily stats"))?;
+        guard.get(&symbol).filter(|stats| stats.date == today()).cloned()
+    };
+    Ok(stats.map(|stats| to_display(&app, stats)))
+}
+
+// 某品种今天的统计（原始单位），供托盘提示等内部使用
+pub(crate) fn current<R: Runtime>(app: &AppHandle<R>, symbol: &str) -> Option<DailyStats> {
+    let state = app.state::<DailyStatsState>();
+    let guard = state.0.lock().ok()?;
+    guard.get(symbol).filter(|stats| stats.date == today()).cloned()
+}
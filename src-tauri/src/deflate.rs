@@ -0,0 +1,419 @@
+use flate2::{Decompress, FlushDecompress, Status};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::stats::Traffic;
+
+// 握手时提供的扩展；只解压服务端消息，客户端发送的消息不压缩（RSV1 不置位，协议允许）
+pub(crate) const EXTENSION_OFFER: &str = "permessage-deflate";
+// 每条压缩消息末尾被发送方去掉的同步刷新标记
+const DEFLATE_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+const MAX_HANDSHAKE_BYTES: usize = 64 * 1024;
+const READ_CHUNK: usize = 8192;
+
+// 正在拼接的压缩消息（首帧 RSV1 置位，后续分片为 continuation 帧）
+struct CompressedMessage {
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+// 位于 TLS 与 tungstenite 之间：tungstenite 不支持 permessage-deflate，
+// 这里把服务端的压缩消息解压成普通帧再交给它；服务端未接受扩展时原样透传
+pub(crate) struct DeflateStream<S> {
+    inner: S,
+    // 读完握手响应头之前为 Some，累积响应头用于判断是否协商成功
+    handshake: Option<Vec<u8>>,
+    negotiated: bool,
+    no_context_takeover: bool,
+    inflater: Decompress,
+    max_message_size: usize,
+    // 已读入但不足一帧的字节
+    input: Vec<u8>,
+    // 待交给 tungstenite 的字节
+    output: Vec<u8>,
+    output_pos: usize,
+    message: Option<CompressedMessage>,
+    eof: bool,
+    traffic: Traffic,
+}
+
+impl<S> DeflateStream<S> {
+    pub(crate) fn new(inner: S, max_message_size: usize) -> Self {
+        DeflateStream {
+            inner,
+            handshake: Some(Vec::new()),
+            negotiated: false,
+            no_context_takeover: false,
+            inflater: Decompress::new(false),
+            max_message_size,
+            input: Vec::new(),
+            output: Vec::new(),
+            output_pos: 0,
+            message: None,
+            eof: false,
+            traffic: Traffic::default(),
+        }
+    }
+
+    // 本次连接握手后收到的字节数：线上（压缩）与解压后
+    pub(crate) fn traffic(&self) -> Traffic {
+        self.traffic
+    }
+
+    // 处理新读到的字节：握手阶段原样透传，之后按帧解析
+    fn feed(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let Some(mut header) = self.handshake.take() else {
+            return self.feed_frames(chunk);
+        };
+        let before = header.len();
+        header.extend_from_slice(chunk);
+        let Some(end) = header.windows(4).position(|w| w == b"\r\n\r\n").map(|pos| pos + 4) else {
+            if header.len() > MAX_HANDSHAKE_BYTES {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "handshake response too large"));
+            }
+            self.output.extend_from_slice(chunk);
+            self.handshake = Some(header);
+            return Ok(());
+        };
+        let split = end - before;
+        self.output.extend_from_slice(&chunk[..split]);
+        let (negotiated, no_context_takeover) = parse_extensions(&header[..end]);
+        self.negotiated = negotiated;
+        self.no_context_takeover = no_context_takeover;
+        self.traffic.compressed = negotiated;
+        self.feed_frames(&chunk[split..])
+    }
+
+    fn feed_frames(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.traffic.wire_bytes += chunk.len() as u64;
+        if !self.negotiated {
+            self.traffic.payload_bytes += chunk.len() as u64;
+            self.output.extend_from_slice(chunk);
+            return Ok(());
+        }
+        self.input.extend_from_slice(chunk);
+        let mut offset = 0;
+        while let Some(frame) = parse_frame(&self.input[offset..], self.max_message_size)? {
+            let raw = offset..offset + frame.len;
+            offset += frame.len;
+            let opcode = self.input[raw.start] & 0x0f;
+            let rsv1 = self.input[raw.start] & 0x40 != 0;
+            let fin = self.input[raw.start] & 0x80 != 0;
+            let compressed = match (self.message.is_some(), opcode) {
+                // 控制帧不压缩，可以插在分片之间，原样透传
+                (_, op) if op & 0x08 != 0 => false,
+                (false, 0x00) => false,
+                (false, _) if rsv1 => {
+                    self.message = Some(CompressedMessage { opcode, payload: Vec::new() });
+                    true
+                }
+                (false, _) => false,
+                (true, 0x00) if !rsv1 => true,
+                (true, _) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid frame inside compressed message"));
+                }
+            };
+            if !compressed {
+                self.traffic.payload_bytes += frame.len as u64;
+                self.output.extend_from_slice(&self.input[raw]);
+                continue;
+            }
+            let payload = unmask(&self.input[raw.start + frame.header_len..raw.end], frame.mask);
+            let Some(message) = self.message.as_mut() else {
+                continue;
+            };
+            if message.payload.len() + payload.len() > self.max_message_size {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "compressed message too large"));
+            }
+            message.payload.extend_from_slice(&payload);
+            if fin {
+                if let Some(message) = self.message.take() {
+                    let text = self.inflate(message.payload)?;
+                    let len = self.output.len();
+                    write_frame(&mut self.output, message.opcode, &text);
+                    self.traffic.payload_bytes += (self.output.len() - len) as u64;
+                }
+            }
+        }
+        self.input.drain(..offset);
+        Ok(())
+    }
+
+    fn inflate(&mut self, mut payload: Vec<u8>) -> io::Result<Vec<u8>> {
+        payload.extend_from_slice(&DEFLATE_TAIL);
+        let mut out = Vec::with_capacity(payload.len() * 4);
+        let start = self.inflater.total_in();
+        loop {
+            let consumed = (self.inflater.total_in() - start) as usize;
+            let status = self
+                .inflater
+                .decompress_vec(&payload[consumed..], &mut out, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if out.len() > self.max_message_size {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "inflated message too large"));
+            }
+            // 发送方用了 BFINAL 块结束消息时，下一条消息是新的压缩流
+            if status == Status::StreamEnd {
+                self.inflater.reset(false);
+                break;
+            }
+            let consumed = (self.inflater.total_in() - start) as usize;
+            if consumed == payload.len() && out.len() < out.capacity() {
+                break;
+            }
+            out.reserve(out.capacity().max(READ_CHUNK));
+        }
+        if self.no_context_takeover {
+            self.inflater.reset(false);
+        }
+        Ok(out)
+    }
+}
+
+// 响应头中服务端是否接受了 permessage-deflate，以及是否要求每条消息重置字典
+fn parse_extensions(header: &[u8]) -> (bool, bool) {
+    let header = String::from_utf8_lossy(header);
+    let accepted = header
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+        .flat_map(|(_, value)| value.split(',').map(str::to_string).collect::<Vec<_>>())
+        .find(|extension| extension.split(';').next().is_some_and(|name| name.trim() == EXTENSION_OFFER));
+    match accepted {
+        Some(extension) => (true, extension.split(';').any(|param| param.trim() == "server_no_context_takeover")),
+        None => (false, false),
+    }
+}
+
+struct FrameInfo {
+    len: usize,
+    header_len: usize,
+    mask: Option<[u8; 4]>,
+}
+
+// 缓冲区开头是否已有完整一帧
+fn parse_frame(data: &[u8], max_size: usize) -> io::Result<Option<FrameInfo>> {
+    if data.len() < 2 {
+        return Ok(None);
+    }
+    let masked = data[1] & 0x80 != 0;
+    let (payload_len, mut header_len) = match data[1] & 0x7f {
+        126 if data.len() >= 4 => (u16::from_be_bytes([data[2], data[3]]) as u64, 4),
+        127 if data.len() >= 10 => (u64::from_be_bytes(data[2..10].try_into().unwrap_or_default()), 10),
+        126 | 127 => return Ok(None),
+        len => (len as u64, 2),
+    };
+    if payload_len > max_size as u64 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too large"));
+    }
+    let mask = if masked {
+        let Some(key) = data.get(header_len..header_len + 4) else {
+            return Ok(None);
+        };
+        header_len += 4;
+        Some([key[0], key[1], key[2], key[3]])
+    } else {
+        None
+    };
+    let len = header_len + payload_len as usize;
+    Ok((data.len() >= len).then_some(FrameInfo { len, header_len, mask }))
+}
+
+fn unmask(payload: &[u8], mask: Option<[u8; 4]>) -> Vec<u8> {
+    match mask {
+        Some(key) => payload.iter().enumerate().map(|(i, byte)| byte ^ key[i % 4]).collect(),
+        None => payload.to_vec(),
+    }
+}
+
+// 写出一个不分片、不加掩码的数据帧
+fn write_frame(out: &mut Vec<u8>, opcode: u8, payload: &[u8]) {
+    out.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => out.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            out.push(126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(payload);
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.output_pos < this.output.len() {
+                let n = buf.remaining().min(this.output.len() - this.output_pos);
+                buf.put_slice(&this.output[this.output_pos..this.output_pos + n]);
+                this.output_pos += n;
+                if this.output_pos == this.output.len() {
+                    this.output.clear();
+                    this.output_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if this.eof {
+                return Poll::Ready(Ok(()));
+            }
+            let mut chunk = [0u8; READ_CHUNK];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf) {
+                Poll::Ready(Ok(())) if chunk_buf.filled().is_empty() => this.eof = true,
+                Poll::Ready(Ok(())) => {
+                    let filled = chunk_buf.filled().to_vec();
+                    this.feed(&filled)?;
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+    use futures_util::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio_tungstenite::tungstenite::{handshake::derive_accept_key, Message};
+
+    // 按 permessage-deflate 压缩一条消息：同步刷新后去掉末尾的 00 00 ff ff
+    fn compress(compressor: &mut Compress, text: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len() + 64);
+        compressor.compress_vec(text.as_bytes(), &mut out, FlushCompress::Sync).unwrap();
+        assert!(out.ends_with(&DEFLATE_TAIL));
+        out.truncate(out.len() - DEFLATE_TAIL.len());
+        out
+    }
+
+    fn frame(first_byte: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_frame(&mut out, 0, payload);
+        out[0] = first_byte;
+        out
+    }
+
+    // 读取客户端握手请求，回复 101 和给定的扩展头，再紧跟着发出 frames
+    async fn serve(mut server: DuplexStream, extensions: Option<&str>, frames: Vec<u8>) -> String {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let read = server.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..read]);
+        }
+        let request = String::from_utf8(request).unwrap();
+        let key = request
+            .lines()
+            .find_map(|line| line.strip_prefix("Sec-WebSocket-Key: "))
+            .unwrap();
+        let mut response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n",
+            derive_accept_key(key.as_bytes())
+        );
+        if let Some(extensions) = extensions {
+            response.push_str(&format!("Sec-WebSocket-Extensions: {extensions}\r\n"));
+        }
+        let mut bytes = format!("{response}\r\n").into_bytes();
+        bytes.extend_from_slice(&frames);
+        server.write_all(&bytes).await.unwrap();
+        // 保持连接，直到客户端读完
+        let _ = server.read(&mut buf).await;
+        request
+    }
+
+    async fn connect(client: DuplexStream) -> tokio_tungstenite::WebSocketStream<DeflateStream<DuplexStream>> {
+        let request = tokio_tungstenite::tungstenite::handshake::client::Request::builder()
+            .uri("ws://feed.test/data")
+            .header("Host", "feed.test")
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
+            .header("Sec-WebSocket-Extensions", EXTENSION_OFFER)
+            .body(())
+            .unwrap();
+        let (ws_stream, _) = tokio_tungstenite::client_async_with_config(request, DeflateStream::new(client, 1 << 20), None)
+            .await
+            .unwrap();
+        ws_stream
+    }
+
+    #[tokio::test]
+    async fn inflates_compressed_messages() {
+        let mut compressor = Compress::new(Compression::default(), false);
+        let first = r#"{"data":{"key":"WG-XAUUSD","bid":2345.6,"ask":2346.1}}"#;
+        let second = r#"{"data":{"key":"WG-XAUUSD","bid":2345.7,"ask":2346.2}}"#;
+        let mut frames = frame(0xc1, &compress(&mut compressor, first));
+        // 第二条沿用上一条的字典（context takeover），分两片发送，中间插一个 Ping
+        let compressed = compress(&mut compressor, second);
+        let (head, tail) = compressed.split_at(compressed.len() / 2);
+        frames.extend(frame(0x41, head));
+        frames.extend(frame(0x89, b"hb"));
+        frames.extend(frame(0x80, tail));
+        frames.extend(frame(0x81, b"plain"));
+        let wire_bytes = frames.len() as u64;
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(serve(server, Some("permessage-deflate; client_max_window_bits=15"), frames));
+        let mut ws_stream = connect(client).await;
+        assert_eq!(ws_stream.next().await.unwrap().unwrap(), Message::Text(first.to_string()));
+        assert!(matches!(ws_stream.next().await.unwrap().unwrap(), Message::Ping(_)));
+        assert_eq!(ws_stream.next().await.unwrap().unwrap(), Message::Text(second.to_string()));
+        assert_eq!(ws_stream.next().await.unwrap().unwrap(), Message::Text("plain".to_string()));
+
+        let traffic = ws_stream.get_ref().traffic();
+        assert!(traffic.compressed);
+        assert_eq!(traffic.wire_bytes, wire_bytes);
+        assert!(traffic.payload_bytes > traffic.wire_bytes);
+        drop(ws_stream);
+        assert!(server.await.unwrap().to_ascii_lowercase().contains("sec-websocket-extensions: permessage-deflate\r\n"));
+    }
+
+    #[tokio::test]
+    async fn passes_through_when_server_declines() {
+        let frames = frame(0x81, b"uncompressed");
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(serve(server, None, frames.clone()));
+        let mut ws_stream = connect(client).await;
+        assert_eq!(ws_stream.next().await.unwrap().unwrap(), Message::Text("uncompressed".to_string()));
+
+        let traffic = ws_stream.get_ref().traffic();
+        assert!(!traffic.compressed);
+        assert_eq!(traffic.wire_bytes, frames.len() as u64);
+        assert_eq!(traffic.payload_bytes, traffic.wire_bytes);
+        drop(ws_stream);
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn parses_accepted_extension() {
+        let header = b"HTTP/1.1 101 Switching Protocols\r\nsec-websocket-extensions: permessage-deflate; server_no_context_takeover\r\n\r\n";
+        assert_eq!(parse_extensions(header), (true, true));
+        let header = b"HTTP/1.1 101 Switching Protocols\r\nSec-WebSocket-Extensions: x-webkit-deflate-frame\r\n\r\n";
+        assert_eq!(parse_extensions(header), (false, false));
+    }
+}
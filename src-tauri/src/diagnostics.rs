@@ -0,0 +1,106 @@
+use futures_util::future::join_all;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+
+use crate::{
+    bank::BANK_SOURCES, connect_async_with_config, current_insecure_tls, current_proxy, current_ws_config, current_ws_url, error::AppError,
+    http_client, log, LogLevel,
+};
+
+// 单个地址的检测超时
+const ENDPOINT_TIMEOUT_MS: u64 = 5000;
+
+// 国际金价的 HTTP 接口（银行接口取自 bank::BANK_SOURCES）
+const XAU_REST_ENDPOINT: (&str, &str) = ("国际金价", "https://api.jdjygold.com/gw/generic/hj/h5/m/queryGjjjLatestPrice");
+
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct EndpointReport {
+    pub name: String,
+    pub url: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl EndpointReport {
+    fn new(name: &str, url: &str, started: Instant, result: Result<(), AppError>) -> Self {
+        let latency_ms = result.is_ok().then(|| started.elapsed().as_millis() as u64);
+        EndpointReport {
+            name: name.to_string(),
+            url: url.to_string(),
+            reachable: result.is_ok(),
+            latency_ms,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+// 单独建立一次 WebSocket 握手后立即关闭，不影响正在运行的行情连接
+async fn probe_websocket<R: Runtime>(app: &AppHandle<R>, url: &str) -> Result<(), AppError> {
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(current_insecure_tls(app))
+        .build()
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    let proxy = current_proxy(app);
+    let (ws_config, disable_nagle) = current_ws_config(app);
+    let connect = connect_async_with_config(
+        url,
+        Some(ws_config),
+        disable_nagle,
+        Some(tokio_tungstenite::Connector::NativeTls(connector)),
+        proxy.as_ref(),
+    );
+    let (mut ws_stream, _) = tokio::time::timeout(Duration::from_millis(ENDPOINT_TIMEOUT_MS), connect)
+        .await
+        .map_err(|_| AppError::Network("Handshake timed out".to_string()))??;
+    let _ = ws_stream.close(None).await;
+    Ok(())
+}
+
+// HEAD 请求只判断能否连通，服务端返回任何状态码都视为可达
+async fn probe_http(client: &reqwest::Client, url: &str) -> Result<(), AppError> {
+    client.head(url).send().await.map(|_| ()).map_err(|e| {
+        if e.is_timeout() {
+            AppError::Network("Request timed out".to_string())
+        } else {
+            e.into()
+        }
+    })
+}
+
+// 检测行情 WebSocket 和各银行接口的连通性，返回每个地址的结果
+#[tauri::command]
+pub(crate) async fn test_endpoints<R: Runtime>(app: AppHandle<R>) -> Result<Vec<EndpointReport>, AppError> {
+    let ws_url = current_ws_url(&app);
+    let client = http_client(&app, ENDPOINT_TIMEOUT_MS)?;
+
+    let websocket = async {
+        let started = Instant::now();
+        let result = probe_websocket(&app, &ws_url).await;
+        EndpointReport::new("WebSocket", &ws_url, started, result)
+    };
+    let endpoints = std::iter::once(XAU_REST_ENDPOINT).chain(BANK_SOURCES.iter().map(|source| (source.label, source.url)));
+    let banks = join_all(endpoints.map(|(name, url)| {
+        let client = &client;
+        async move {
+            let started = Instant::now();
+            let result = probe_http(client, url).await;
+            EndpointReport::new(name, url, started, result)
+        }
+    }));
+    let (websocket, banks) = tokio::join!(websocket, banks);
+
+    let reports: Vec<EndpointReport> = std::iter::once(websocket).chain(banks).collect();
+    for report in &reports {
+        match &report.error {
+            None => log(LogLevel::Info, &format!(
+                "test_endpoints: {} reachable in {}ms",
+                report.url,
+                report.latency_ms.unwrap_or_default()
+            )),
+            Some(e) => log(LogLevel::Warn, &format!("test_endpoints: {} unreachable: {}", report.url, e)),
+        }
+    }
+    Ok(reports)
+}
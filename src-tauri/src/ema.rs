@@ -0,0 +1,56 @@
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{log, tick::PriceTick, AppSettings, LogLevel};
+
+// 两次行情间隔超过这个时间（如断线重连）时从新价格重新开始计算
+const EMA_RESET_GAP_SECS: u64 = 60;
+
+struct EmaEntry {
+    value: f64,
+    period: u32,
+    updated: Instant,
+}
+
+// 每个品种的指数移动平均（按买价计算）
+#[derive(Default)]
+pub(crate) struct EmaState(Mutex<HashMap<String, EmaEntry>>);
+
+// 用最新行情更新 EMA；未设置 ema_period 时清空
+pub(crate) fn update<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    let period = app.state::<AppSettings>().0.read().ema_period.filter(|period| *period > 0);
+    let state = app.state::<EmaState>();
+    let Ok(mut guard) = state.0.lock() else {
+        return;
+    };
+    let Some(period) = period else {
+        guard.clear();
+        return;
+    };
+    let now = Instant::now();
+    let entry = guard.entry(tick.symbol.clone()).or_insert(EmaEntry {
+        value: tick.bid,
+        period,
+        updated: now,
+    });
+    let gap = now.duration_since(entry.updated).as_secs();
+    if entry.period != period || gap > EMA_RESET_GAP_SECS {
+        if gap > EMA_RESET_GAP_SECS {
+            log(LogLevel::Info, &format!("ema: reset {} after {}s without ticks", tick.symbol, gap));
+        }
+        entry.value = tick.bid;
+        entry.period = period;
+    } else {
+        let alpha = 2.0 / (period as f64 + 1.0);
+        entry.value += alpha * (tick.bid - entry.value);
+    }
+    entry.updated = now;
+}
+
+// 某品种当前的 EMA
+pub(crate) fn current<R: Runtime>(app: &AppHandle<R>, symbol: &str) -> Option<f64> {
+    app.state::<AppSettings>().0.read().ema_period?;
+    let state = app.state::<EmaState>();
+    let guard = state.0.lock().ok()?;
+    guard.get(symbol).map(|entry| entry.value)
+}
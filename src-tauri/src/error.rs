@@ -0,0 +1,114 @@
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::fmt;
+
+// 命令返回给前端的错误，序列化为 { kind, message }，前端按 kind 区分处理
+#[derive(Debug, Clone)]
+pub(crate) enum AppError {
+    // 内部锁被污染，参数为锁的名称
+    LockPoisoned(&'static str),
+    // 参数或设置不合法
+    Validation(String),
+    // 请求的方案、主题、品种等不存在
+    NotFound(String),
+    // HTTP 或 WebSocket 请求失败
+    Network(String),
+    // WebSocket 未连接或任务未运行
+    NotConnected,
+    // 读写设置存储失败
+    Store(String),
+    // 行情历史数据库出错
+    Database(String),
+    // 文件读写失败
+    Io(String),
+    // 窗口、快捷键、开机自启等系统接口调用失败
+    Platform(String),
+    // 其他不应发生的内部错误（如序列化失败）
+    Internal(String),
+}
+
+impl AppError {
+    fn kind(&self) -> &'static str {
+        match self {
+            AppError::LockPoisoned(_) => "LockPoisoned",
+            AppError::Validation(_) => "Validation",
+            AppError::NotFound(_) => "NotFound",
+            AppError::Network(_) => "Network",
+            AppError::NotConnected => "NotConnected",
+            AppError::Store(_) => "Store",
+            AppError::Database(_) => "Database",
+            AppError::Io(_) => "Io",
+            AppError::Platform(_) => "Platform",
+            AppError::Internal(_) => "Internal",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::LockPoisoned(name) => write!(f, "{name} lock poisoned"),
+            AppError::NotConnected => f.write_str("WebSocket is not connected"),
+            AppError::Validation(message)
+            | AppError::NotFound(message)
+            | AppError::Network(message)
+            | AppError::Store(message)
+            | AppError::Database(message)
+            | AppError::Io(message)
+            | AppError::Platform(message)
+            | AppError::Internal(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<tauri_plugin_store::Error> for AppError {
+    fn from(e: tauri_plugin_store::Error) -> Self {
+        AppError::Store(e.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::Network(e.to_string())
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for AppError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        AppError::Network(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Platform(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{log, stats, LogLevel};
+
+// 请求头最多读取的字节数
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+// 正在运行的健康检查服务（端口和任务句柄）
+#[derive(Default)]
+pub(crate) struct HealthServer(Mutex<Option<(u16, tauri::async_runtime::JoinHandle<()>)>>);
+
+// 按 health_port 启动、切换或关闭本地健康检查服务
+pub(crate) fn apply_health_port<R: Runtime>(app: &AppHandle<R>, port: Option<u16>) {
+    let server = app.state::<HealthServer>();
+    let Ok(mut guard) = server.0.lock() else {
+        log(LogLevel::Error, "health: server lock poisoned");
+        return;
+    };
+    if guard.as_ref().map(|(current, _)| *current) == port {
+        return;
+    }
+    if let Some((old_port, handle)) = guard.take() {
+        handle.abort();
+        log(LogLevel::Info, &format!("health: stopped listening on port {}", old_port));
+    }
+    if let Some(port) = port {
+        let app = app.clone();
+        let handle = tauri::async_runtime::spawn(async move { serve(app, port).await });
+        *guard = Some((port, handle));
+    }
+}
+
+async fn serve<R: Runtime>(app: AppHandle<R>, port: u16) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log(LogLevel::Error, &format!("health: bind 127.0.0.1:{} failed: {}", port, e));
+            return;
+        }
+    };
+    log(LogLevel::Info, &format!("health: listening on http://127.0.0.1:{}/health", port));
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = handle_connection(&app, stream).await {
+                        log(LogLevel::Warn, &format!("health: request failed: {}", e));
+                    }
+                });
+            }
+            Err(e) => log(LogLevel::Warn, &format!("health: accept failed: {}", e)),
+        }
+    }
+}
+
+// 只处理 GET /health：在线返回 200，断开返回 503，正文为 ConnectionStats
+async fn handle_connection<R: Runtime>(app: &AppHandle<R>, mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let (status, body) = match (method, path.split('?').next().unwrap_or_default()) {
+        ("GET", "/health") => match stats::snapshot(app) {
+            Ok(stats) => {
+                let status = if stats.connected { "200 OK" } else { "503 Service Unavailable" };
+                (status, serde_json::to_string(&stats).unwrap_or_default())
+            }
+            Err(e) => ("500 Internal Server Error", serde_json::json!({ "error": e }).to_string()),
+        },
+        (_, "/health") => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
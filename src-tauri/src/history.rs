@@ -0,0 +1,534 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::{alerts::AlertTriggered, error::AppError, log, logging, tick, tick::PriceTick, AppSettings, LogLevel};
+
+const HISTORY_DB_FILE: &str = "history.sqlite";
+// 攒够条数或超过间隔才写一次盘
+const HISTORY_BATCH_SIZE: usize = 50;
+const HISTORY_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_QUERY_ROWS: usize = 100_000;
+const MAX_CANDLES: usize = 100_000;
+pub(crate) const DEFAULT_ALERT_HISTORY_DAYS: u32 = 30;
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Default)]
+struct HistoryInner {
+    conn: Option<Connection>,
+    pending: Vec<PriceTick>,
+    last_flush: Option<Instant>,
+}
+
+// 行情历史数据库，首次写入时打开（history_enabled 关闭时行情不写入，但告警记录仍会写入）
+#[derive(Default)]
+pub(crate) struct HistoryDb(Mutex<HistoryInner>);
+
+fn db_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    let dir = app.path().app_data_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(HISTORY_DB_FILE))
+}
+
+fn open_db<R: Runtime>(app: &AppHandle<R>) -> Result<Connection, AppError> {
+    let conn = Connection::open(db_path(app)?)?;
+    // WAL 模式下写入中途崩溃不会损坏已提交的数据
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ticks (
+            symbol TEXT NOT NULL,
+            bid REAL NOT NULL,
+            ask REAL NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ticks_symbol_ts ON ticks (symbol, timestamp);
+        CREATE TABLE IF NOT EXISTS alert_events (
+            symbol TEXT NOT NULL,
+            direction TEXT NOT NULL,
+            threshold REAL NOT NULL,
+            price REAL NOT NULL,
+            delta REAL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_alert_events_ts ON alert_events (timestamp);",
+    )?;
+    Ok(conn)
+}
+
+fn history_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<AppSettings>().0.read().history_enabled
+}
+
+// 把待写入的行情在一个事务里写入数据库
+fn flush<R: Runtime>(app: &AppHandle<R>, inner: &mut HistoryInner) -> Result<(), AppError> {
+    inner.last_flush = Some(Instant::now());
+    if inner.pending.is_empty() {
+        return Ok(());
+    }
+    if inner.conn.is_none() {
+        inner.conn = Some(open_db(app)?);
+    }
+    let Some(conn) = inner.conn.as_mut() else {
+        return Ok(());
+    };
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx
+            .prepare_cached("INSERT INTO ticks (symbol, bid, ask, timestamp) VALUES (?1, ?2, ?3, ?4)")?;
+        for tick in &inner.pending {
+            stmt.execute(params![tick.symbol, tick.bid, tick.ask, tick.timestamp as i64])?;
+        }
+    }
+    tx.commit()?;
+    inner.pending.clear();
+    Ok(())
+}
+
+// 记录一条行情，批量写入
+pub(crate) fn record<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    if !history_enabled(app) {
+        return;
+    }
+    let db = app.state::<HistoryDb>();
+    let Ok(mut guard) = db.0.lock() else {
+        return;
+    };
+    guard.pending.push(tick.clone());
+    let due = guard.pending.len() >= HISTORY_BATCH_SIZE
+        || guard
+            .last_flush
+            .is_none_or(|at| at.elapsed() >= HISTORY_FLUSH_INTERVAL);
+    if due {
+        if let Err(e) = flush(app, &mut guard) {
+            log(LogLevel::Error, &format!("history: write failed: {}", e));
+            // 写入失败时丢弃这一批，避免无限堆积
+            guard.pending.clear();
+        }
+    }
+}
+
+// 退出前写入缓冲中的行情并关闭数据库
+pub(crate) fn close<R: Runtime>(app: &AppHandle<R>) {
+    let db = app.state::<HistoryDb>();
+    let Ok(mut guard) = db.0.lock() else {
+        return;
+    };
+    if let Err(e) = flush(app, &mut guard) {
+        log(LogLevel::Error, &format!("history: final write failed: {}", e));
+    }
+    if let Some(conn) = guard.conn.take() {
+        if let Err((_, e)) = conn.close() {
+            log(LogLevel::Error, &format!("history: close failed: {}", e));
+        }
+    }
+}
+
+// 查询前先写入缓冲中的行情，保证读到最新数据
+fn open_for_query<'a, R: Runtime>(
+    app: &AppHandle<R>,
+    state: &'a State<HistoryDb>,
+) -> Result<std::sync::MutexGuard<'a, HistoryInner>, AppError> {
+    if !history_enabled(app) {
+        return Err(AppError::Validation("Tick history is disabled".to_string()));
+    }
+    let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("History"))?;
+    flush(app, &mut guard)?;
+    if guard.conn.is_none() {
+        guard.conn = Some(open_db(app)?);
+    }
+    Ok(guard)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// 导出时间范围内的行情为 CSV 文件，逐行写入，返回导出的行数
+#[tauri::command]
+pub(crate) fn export_ticks_csv<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<HistoryDb>,
+    symbol: String,
+    from_ts: u64,
+    to_ts: u64,
+    path: String,
+) -> Result<u64, AppError> {
+    let guard = open_for_query(&app, &state)?;
+    let Some(conn) = guard.conn.as_ref() else {
+        return Ok(0);
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT symbol, bid, ask, timestamp FROM ticks
+             WHERE symbol = ?1 AND timestamp BETWEEN ?2 AND ?3
+             ORDER BY timestamp",
+        )?;
+    let mut rows = stmt
+        .query(params![symbol, from_ts as i64, to_ts as i64])?;
+    
+    let file = File::create(&path).map_err(|e| AppError::Io(format!("Failed to create {path}: {e}")))?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "symbol,bid,ask,timestamp")?;
+    let mut count: u64 = 0;
+    while let Some(row) = rows.next()? {
+        let symbol: String = row.get(0)?;
+        let bid: f64 = row.get(1)?;
+        let ask: f64 = row.get(2)?;
+        let timestamp: i64 = row.get(3)?;
+        writeln!(writer, "{},{},{},{}", csv_field(&symbol), bid, ask, timestamp)?;
+        count += 1;
+    }
+    writer.flush()?;
+    log(LogLevel::Info, &format!("export_ticks_csv: {} rows to {}", count, path));
+    Ok(count)
+}
+
+// K 线（按买价聚合），start_ts 为区间起点的毫秒时间戳
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct Candle {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub start_ts: u64,
+}
+
+// 把按时间排序的 (时间戳, 价格) 聚合为 K 线；fill_gaps 为真时空区间沿用上一根的收盘价
+fn aggregate_candles(prices: impl Iterator<Item = (u64, f64)>, interval_ms: u64, fill_gaps: bool) -> Vec<Candle> {
+    let mut candles: Vec<Candle> = Vec::new();
+    for (timestamp, price) in prices {
+        let start_ts = timestamp - timestamp % interval_ms;
+        if let Some(last) = candles.last_mut() {
+            if last.start_ts == start_ts {
+                last.high = last.high.max(price);
+                last.low = last.low.min(price);
+                last.close = price;
+                continue;
+            }
+            if fill_gaps {
+                let close = last.close;
+                let mut gap_ts = last.start_ts + interval_ms;
+                while gap_ts < start_ts && candles.len() < MAX_CANDLES {
+                    candles.push(Candle {
+                        open: close,
+                        high: close,
+                        low: close,
+                        close,
+                        start_ts: gap_ts,
+                    });
+                    gap_ts += interval_ms;
+                }
+            }
+        }
+        if candles.len() >= MAX_CANDLES {
+            break;
+        }
+        candles.push(Candle {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            start_ts,
+        });
+    }
+    candles
+}
+
+// 按 interval_secs 聚合时间范围内的行情为 K 线，fill_gaps 缺省为 false（跳过空区间）
+#[tauri::command]
+pub(crate) fn get_candles<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<HistoryDb>,
+    symbol: String,
+    interval_secs: u64,
+    from_ts: u64,
+    to_ts: u64,
+    fill_gaps: Option<bool>,
+) -> Result<Vec<Candle>, AppError> {
+    if interval_secs == 0 {
+        return Err(AppError::Validation("interval_secs must be greater than 0".to_string()));
+    }
+    let guard = open_for_query(&app, &state)?;
+    let Some(conn) = guard.conn.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, bid FROM ticks
+             WHERE symbol = ?1 AND timestamp BETWEEN ?2 AND ?3
+             ORDER BY timestamp",
+        )?;
+    let rows = stmt
+        .query_map(params![symbol, from_ts as i64, to_ts as i64], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get::<_, f64>(1)?))
+        })?;
+    let mut error = None;
+    let prices = rows.map_while(|row| match row {
+        Ok(price) => Some(price),
+        Err(e) => {
+            error = Some(AppError::from(e));
+            None
+        }
+    });
+    let candles = aggregate_candles(prices, interval_secs.saturating_mul(1000), fill_gaps.unwrap_or(false));
+    match error {
+        Some(e) => Err(e),
+        None => Ok(candles),
+    }
+}
+
+// 查询某品种在时间范围内的行情（毫秒时间戳，含两端）
+#[tauri::command]
+pub(crate) fn query_ticks<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<HistoryDb>,
+    symbol: String,
+    from_ts: u64,
+    to_ts: u64,
+) -> Result<Vec<PriceTick>, AppError> {
+    let guard = open_for_query(&app, &state)?;
+    let Some(conn) = guard.conn.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT symbol, bid, ask, timestamp FROM ticks
+             WHERE symbol = ?1 AND timestamp BETWEEN ?2 AND ?3
+             ORDER BY timestamp LIMIT ?4",
+        )?;
+    let rows = stmt
+        .query_map(
+            params![symbol, from_ts as i64, to_ts as i64, MAX_QUERY_ROWS as i64],
+            |row| {
+                Ok(PriceTick {
+                    symbol: row.get(0)?,
+                    bid: row.get(1)?,
+                    ask: row.get(2)?,
+                    timestamp: row.get::<_, i64>(3)? as u64,
+                })
+            },
+        )?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+// 一条已触发的告警记录
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct AlertRecord {
+    pub symbol: String,
+    pub direction: String,
+    pub threshold: f64,
+    pub price: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<f64>,
+    pub timestamp: u64,
+}
+
+// 已打开的连接，没有则打开
+fn connection<'a, R: Runtime>(app: &AppHandle<R>, inner: &'a mut HistoryInner) -> Result<&'a Connection, AppError> {
+    let conn = match inner.conn.take() {
+        Some(conn) => conn,
+        None => open_db(app)?,
+    };
+    Ok(inner.conn.insert(conn))
+}
+
+// 告警很少触发，逐条立即写入，不受 history_enabled 影响
+pub(crate) fn record_alert<R: Runtime>(app: &AppHandle<R>, event: &AlertTriggered) {
+    let db = app.state::<HistoryDb>();
+    let Ok(mut guard) = db.0.lock() else {
+        return;
+    };
+    let result = connection(app, &mut guard).and_then(|conn| {
+        conn.prepare_cached(
+            "INSERT INTO alert_events (symbol, direction, threshold, price, delta, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            event.symbol,
+            event.direction,
+            event.threshold,
+            event.price,
+            event.delta,
+            event.timestamp as i64
+        ])?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        log(LogLevel::Error, &format!("history: record alert failed: {}", e));
+    }
+}
+
+// 启动时删除超过保留天数的告警记录；数据库还不存在时什么都不做，保留天数为 0 表示永久保留
+pub(crate) fn prune_alert_history<R: Runtime>(app: &AppHandle<R>) {
+    let days = app.state::<AppSettings>().0.read().alert_history_days;
+    if days == 0 || !db_path(app).is_ok_and(|path| path.exists()) {
+        return;
+    }
+    let cutoff = tick::now_millis().saturating_sub(u64::from(days) * DAY_MS);
+    let db = app.state::<HistoryDb>();
+    let Ok(mut guard) = db.0.lock() else {
+        return;
+    };
+    let result = connection(app, &mut guard).and_then(|conn| {
+        Ok(conn.execute("DELETE FROM alert_events WHERE timestamp < ?1", params![cutoff as i64])?)
+    });
+    match result {
+        Ok(0) => {}
+        Ok(removed) => log(LogLevel::Info, &format!("history: pruned {} alert records older than {} days", removed, days)),
+        Err(e) => log(LogLevel::Error, &format!("history: prune alerts failed: {}", e)),
+    }
+}
+
+// 查询时间范围内触发过的告警（毫秒时间戳，含两端）
+#[tauri::command]
+pub(crate) fn get_alert_history<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<HistoryDb>,
+    from_ts: u64,
+    to_ts: u64,
+) -> Result<Vec<AlertRecord>, AppError> {
+    let mut guard = state.0.lock().map_err(|_| AppError::LockPoisoned("History"))?;
+    let conn = connection(&app, &mut guard)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT symbol, direction, threshold, price, delta, timestamp FROM alert_events
+             WHERE timestamp BETWEEN ?1 AND ?2
+             ORDER BY timestamp LIMIT ?3",
+        )?;
+    let rows = stmt
+        .query_map(
+            params![from_ts as i64, to_ts as i64, MAX_QUERY_ROWS as i64],
+            |row| {
+                Ok(AlertRecord {
+                    symbol: row.get(0)?,
+                    direction: row.get(1)?,
+                    threshold: row.get(2)?,
+                    price: row.get(3)?,
+                    delta: row.get(4)?,
+                    timestamp: row.get::<_, i64>(5)? as u64,
+                })
+            },
+        )?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+// 启动时用 PRAGMA quick_check 检查数据库，损坏时改名备份（连同 -wal/-shm）并重建空库
+pub(crate) fn check_integrity<R: Runtime>(app: &AppHandle<R>) {
+    let Ok(path) = db_path(app) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    let result = Connection::open(&path).and_then(|conn| {
+        let rows = conn
+            .prepare("PRAGMA quick_check")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    });
+    let problem = match result {
+        Ok(rows) if rows.len() == 1 && rows[0] == "ok" => {
+            log(LogLevel::Info, "history: integrity check ok");
+            return;
+        }
+        Ok(rows) => rows.join("; "),
+        Err(e) => e.to_string(),
+    };
+    let backup_suffix = format!("corrupt-{}", tick::now_millis() / 1000);
+    for suffix in ["", "-wal", "-shm"] {
+        let file = path.with_file_name(format!("{HISTORY_DB_FILE}{suffix}"));
+        if file.exists() {
+            let backup = path.with_file_name(format!("{HISTORY_DB_FILE}{suffix}.{backup_suffix}"));
+            if let Err(e) = std::fs::rename(&file, &backup) {
+                log(LogLevel::Error, &format!("history: backup {} failed: {}", file.display(), e));
+                return;
+            }
+        }
+    }
+    match open_db(app) {
+        Ok(_) => log(LogLevel::Error, &format!(
+            "history: integrity check failed ({}), moved old database aside and rebuilt",
+            logging::truncate_utf8(&problem, 500)
+        )),
+        Err(e) => log(LogLevel::Error, &format!("history: integrity check failed ({}), rebuild failed: {}", problem, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: u64 = 60_000;
+
+    fn ohlc(candles: &[Candle]) -> Vec<(u64, f64, f64, f64, f64)> {
+        candles
+            .iter()
+            .map(|candle| (candle.start_ts, candle.open, candle.high, candle.low, candle.close))
+            .collect()
+    }
+
+    #[test]
+    fn aggregates_ohlc_within_bucket() {
+        let prices = [(1_000, 10.0), (2_000, 12.5), (3_000, 9.0), (59_999, 11.0)];
+        let candles = aggregate_candles(prices.into_iter(), MINUTE_MS, false);
+        assert_eq!(ohlc(&candles), vec![(0, 10.0, 12.5, 9.0, 11.0)]);
+    }
+
+    #[test]
+    fn splits_at_bucket_boundaries() {
+        // 区间左闭右开：60_000 属于第二根
+        let prices = [(59_999, 10.0), (60_000, 11.0), (119_999, 12.0), (120_000, 13.0)];
+        let candles = aggregate_candles(prices.into_iter(), MINUTE_MS, false);
+        assert_eq!(
+            ohlc(&candles),
+            vec![
+                (0, 10.0, 10.0, 10.0, 10.0),
+                (60_000, 11.0, 12.0, 11.0, 12.0),
+                (120_000, 13.0, 13.0, 13.0, 13.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_empty_buckets_without_fill_gaps() {
+        let prices = [(10_000, 10.0), (20_000, 11.0), (190_000, 14.0)];
+        let candles = aggregate_candles(prices.into_iter(), MINUTE_MS, false);
+        assert_eq!(
+            ohlc(&candles),
+            vec![(0, 10.0, 11.0, 10.0, 11.0), (180_000, 14.0, 14.0, 14.0, 14.0)]
+        );
+    }
+
+    #[test]
+    fn fill_gaps_carries_previous_close() {
+        let prices = [(10_000, 10.0), (20_000, 11.0), (190_000, 14.0)];
+        let candles = aggregate_candles(prices.into_iter(), MINUTE_MS, true);
+        assert_eq!(
+            ohlc(&candles),
+            vec![
+                (0, 10.0, 11.0, 10.0, 11.0),
+                (60_000, 11.0, 11.0, 11.0, 11.0),
+                (120_000, 11.0, 11.0, 11.0, 11.0),
+                (180_000, 14.0, 14.0, 14.0, 14.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_prices_no_candles() {
+        assert!(aggregate_candles(std::iter::empty(), MINUTE_MS, true).is_empty());
+    }
+}
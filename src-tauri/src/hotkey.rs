@@ -0,0 +1,84 @@
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::{clipboard, error::AppError, log, toggle_click_through, toggle_window_visibility, AppSettings, LogLevel};
+
+fn parse_hotkey(hotkey: &str) -> Result<Shortcut, AppError> {
+    hotkey
+        .parse::<Shortcut>()
+        .map_err(|e| AppError::Validation(format!("Invalid hotkey '{hotkey}': {e}")))
+}
+
+// 全局快捷键回调：鼠标穿透快捷键切换穿透，复制快捷键复制金价，其余切换窗口显示
+pub(crate) fn handle_shortcut<R: Runtime>(app: &AppHandle<R>, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let (click_through_hotkey, copy_price_hotkey) = {
+        let state = app.state::<AppSettings>();
+        let guard = state.0.read();
+        (guard.click_through_hotkey.clone(), guard.copy_price_hotkey.clone())
+    };
+    let matches = |hotkey: Option<String>| {
+        hotkey
+            .as_deref()
+            .and_then(|hotkey| parse_hotkey(hotkey).ok())
+            .is_some_and(|parsed| parsed == *shortcut)
+    };
+    if matches(click_through_hotkey) {
+        toggle_click_through(app);
+    } else if matches(copy_price_hotkey) {
+        clipboard::copy_xau_price(app);
+    } else {
+        toggle_window_visibility(app);
+    }
+}
+
+// 启动时注册已保存的快捷键，失败只记录日志
+pub(crate) fn register_saved_hotkey<R: Runtime>(app: &AppHandle<R>, hotkey: Option<&str>) {
+    let Some(hotkey) = hotkey else {
+        return;
+    };
+    let result = parse_hotkey(hotkey).and_then(|shortcut| {
+        app.global_shortcut()
+            .register(shortcut)
+            .map_err(|e| AppError::Platform(e.to_string()))
+    });
+    match result {
+        Ok(()) => log(LogLevel::Info, &format!("hotkey: registered {hotkey}")),
+        Err(e) => log(LogLevel::Error, &format!("hotkey: register {hotkey} failed: {e}")),
+    }
+}
+
+// 替换快捷键：先注销旧的再注册新的，新的注册失败时恢复旧的
+pub(crate) fn replace_hotkey<R: Runtime>(
+    app: &AppHandle<R>,
+    old: Option<&str>,
+    new: Option<&str>,
+) -> Result<(), AppError> {
+    let new_shortcut = new.map(parse_hotkey).transpose()?;
+    let old_shortcut = old.and_then(|hotkey| parse_hotkey(hotkey).ok());
+    let manager = app.global_shortcut();
+
+    if let Some(shortcut) = old_shortcut {
+        if manager.is_registered(shortcut) {
+            let _ = manager.unregister(shortcut);
+        }
+    }
+    let Some(shortcut) = new_shortcut else {
+        log(LogLevel::Info, "hotkey: cleared");
+        return Ok(());
+    };
+    if let Err(e) = manager.register(shortcut) {
+        if let Some(old_shortcut) = old_shortcut {
+            let _ = manager.register(old_shortcut);
+        }
+        let hotkey = new.unwrap_or_default();
+        log(LogLevel::Warn, &format!("hotkey: register {hotkey} failed: {e}"));
+        return Err(AppError::Platform(format!(
+            "Hotkey '{hotkey}' is unavailable (already in use by another application?)"
+        )));
+    }
+    log(LogLevel::Info, &format!("hotkey: registered {}", new.unwrap_or_default()));
+    Ok(())
+}
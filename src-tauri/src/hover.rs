@@ -0,0 +1,101 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{log, secondary::MAIN_LABEL, AppSettings, LogLevel};
+
+// 检查鼠标位置的间隔
+const HOVER_POLL_INTERVAL_MS: u64 = 150;
+// 鼠标停留多久后显示、离开多久后隐藏，避免划过时闪烁
+const REVEAL_DELAY_MS: u64 = 300;
+const HIDE_DELAY_MS: u64 = 600;
+// 感应区域在窗口四周额外放宽的逻辑像素（另加上吸附边距，鼠标推到屏幕角落也能触发）
+const HOVER_SLOP: f64 = 8.0;
+
+#[derive(Default)]
+struct HoverInner {
+    // 窗口是否由悬停显示（只隐藏自己显示出来的窗口）
+    revealed: bool,
+    // 鼠标进入或离开感应区域的时间，用于防抖
+    since: Option<Instant>,
+}
+
+#[derive(Default)]
+pub(crate) struct HoverReveal(Mutex<HoverInner>);
+
+// 窗口所在位置（隐藏时保留）四周放宽后的感应区域，物理像素
+fn hover_region<R: Runtime>(window: &tauri::WebviewWindow<R>, snap_margin: f64) -> Option<(f64, f64, f64, f64)> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let slop = (HOVER_SLOP + snap_margin.max(0.0)) * window.scale_factor().unwrap_or(1.0);
+    Some((
+        position.x as f64 - slop,
+        position.y as f64 - slop,
+        position.x as f64 + size.width as f64 + slop,
+        position.y as f64 + size.height as f64 + slop,
+    ))
+}
+
+// 开启 hover_reveal 时，主窗口隐藏期间鼠标移到它所在的角落就显示，移开后再隐藏；
+// 用户手动显示（固定显示）的窗口不受影响
+pub(crate) fn spawn_hover_watcher<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(HOVER_POLL_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            let (enabled, snap_margin) = {
+                let state = app.state::<AppSettings>();
+                let settings = state.0.read();
+                (settings.hover_reveal, settings.snap_margin)
+            };
+            let state = app.state::<HoverReveal>();
+            let Ok(mut guard) = state.0.lock() else {
+                continue;
+            };
+            let Some(window) = app.get_webview_window(MAIN_LABEL) else {
+                continue;
+            };
+            let visible = window.is_visible().unwrap_or(false);
+            // 悬停显示后被托盘或快捷键隐藏，或关闭了该功能时，交还给用户控制
+            if !enabled || !visible {
+                guard.revealed = false;
+            }
+            if !enabled || (visible && !guard.revealed) {
+                guard.since = None;
+                continue;
+            }
+            let (Some((left, top, right, bottom)), Ok(cursor)) = (hover_region(&window, snap_margin), app.cursor_position()) else {
+                continue;
+            };
+            let inside = cursor.x >= left && cursor.x <= right && cursor.y >= top && cursor.y <= bottom;
+            // 未显示时等鼠标进入，已显示时等鼠标离开
+            let waiting_for = if guard.revealed { !inside } else { inside };
+            if !waiting_for {
+                guard.since = None;
+                continue;
+            }
+            let since = *guard.since.get_or_insert_with(Instant::now);
+            let delay = if guard.revealed { HIDE_DELAY_MS } else { REVEAL_DELAY_MS };
+            if since.elapsed() < Duration::from_millis(delay) {
+                continue;
+            }
+            guard.since = None;
+            if guard.revealed {
+                if let Err(e) = window.hide() {
+                    log(LogLevel::Warn, &format!("hover_reveal: hide failed: {}", e));
+                }
+                guard.revealed = false;
+            } else {
+                if let Err(e) = window.show() {
+                    log(LogLevel::Warn, &format!("hover_reveal: show failed: {}", e));
+                    continue;
+                }
+                let always_on_top = app.state::<AppSettings>().0.read().always_on_top;
+                let _ = window.set_always_on_top(always_on_top);
+                guard.revealed = true;
+            }
+        }
+    });
+}
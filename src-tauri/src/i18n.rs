@@ -0,0 +1,55 @@
+use crate::error::AppError;
+
+pub(crate) const DEFAULT_LANGUAGE: &str = "zh-CN";
+const LANGUAGES: &[&str] = &["zh-CN", "en"];
+
+// 托盘菜单文字：键、简体中文、英文（缺少英文时用中文）
+const STRINGS: &[(&str, &str, Option<&str>)] = &[
+    ("show", "显示/隐藏", Some("Show/Hide")),
+    ("show_secondary", "显示/隐藏副窗口", Some("Show/Hide Second Bar")),
+    ("show_platform", "显示", Some("Show")),
+    ("bg_color", "背景颜色", Some("Background Color")),
+    ("theme", "主题", Some("Theme")),
+    ("profiles", "配置方案", Some("Profiles")),
+    ("profile_none", "（无）", Some("(None)")),
+    ("text_color", "文字颜色", Some("Text Color")),
+    ("opacity", "透明度", Some("Opacity")),
+    ("reconnect", "重新连接", Some("Reconnect")),
+    ("pause", "暂停更新", Some("Pause Updates")),
+    ("copy_price", "复制金价", Some("Copy Gold Price")),
+    ("reset_position", "重置位置", Some("Reset Position")),
+    ("snap", "吸附位置", Some("Snap Position")),
+    ("monitor", "显示器", Some("Monitor")),
+    ("Center", "居中", Some("Center")),
+    ("TopLeft", "左上角", Some("Top Left")),
+    ("TopRight", "右上角", Some("Top Right")),
+    ("BottomLeft", "左下角", Some("Bottom Left")),
+    ("BottomRight", "右下角", Some("Bottom Right")),
+    ("insecure_tls", "跳过证书验证", Some("Skip Certificate Verification")),
+    ("always_on_top", "窗口置顶", Some("Always on Top")),
+    ("click_through", "鼠标穿透", Some("Click Through")),
+    ("autostart", "开机自启", Some("Launch at Startup")),
+    ("reset_settings", "恢复默认", Some("Reset Settings")),
+    ("notifications", "告警通知", Some("Alert Notifications")),
+    ("open_data_dir", "打开日志目录", Some("Open Log Folder")),
+    ("quit", "退出", Some("Quit")),
+];
+
+pub(crate) fn validate_language(language: &str) -> Result<(), AppError> {
+    if LANGUAGES.contains(&language) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("Unsupported language: {language}")))
+    }
+}
+
+// 按语言查找菜单文字，缺少对应语言时退回简体中文，键不存在时返回键本身
+pub(crate) fn tr(language: &str, key: &'static str) -> &'static str {
+    let Some((_, zh_cn, en)) = STRINGS.iter().find(|(name, _, _)| *name == key) else {
+        return key;
+    };
+    match language {
+        "en" => en.unwrap_or(zh_cn),
+        _ => zh_cn,
+    }
+}
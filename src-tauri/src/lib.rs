@@ -2,458 +2,2970 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::OpenOptions,
-    io::Write,
-    sync::Mutex,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::{collections::HashMap, sync::Mutex};
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder,
 };
-use tauri_plugin_store::StoreExt;
-use reqwest;
-use tokio_tungstenite::tungstenite::Message;
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio_tungstenite::tungstenite::{protocol::WebSocketConfig, Message};
 use futures_util::{StreamExt, SinkExt};
+use parking_lot::RwLock;
+use rand::Rng;
+
+mod about;
+mod alerts;
+mod attention;
+mod bank;
+mod capture;
+mod clipboard;
+mod colors;
+mod currency;
+mod daily;
+mod deflate;
+mod diagnostics;
+mod ema;
+mod error;
+mod health;
+mod history;
+mod hotkey;
+mod hover;
+mod i18n;
+mod logging;
+mod pause;
+mod persist;
+mod power;
+mod profiles;
+mod proxy;
+mod secondary;
+mod simulate;
+mod sound;
+mod spread;
+mod staleness;
+mod stats;
+mod symbols;
+mod throttle;
+mod tick;
+mod tooltip;
+
+use alerts::{AlertsState, VelocityAlertsState};
+use colors::ColorPresets;
+use currency::{DisplayUnit, FxRefresh};
+use daily::DailyStatsState;
+use ema::EmaState;
+use error::AppError;
+use health::HealthServer;
+use history::HistoryDb;
+use hover::HoverReveal;
+use pause::PauseState;
+use persist::StoreFlush;
+use secondary::SecondaryWindowConfig;
+use staleness::FeedWatch;
+use stats::WsStats;
+use logging::{log, LogLevel};
+use throttle::EmitThrottle;
+use tick::{PriceBounds, PriceTick, RawMessages, TickDedup, TickHistory};
+
+// 一个显示平台：symbol 为行情代码；poll_interval_secs 为空时走 WebSocket 推送，否则由后端按间隔轮询银行接口
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PlatformConfig {
+    id: String,
+    label: String,
+    symbol: Option<String>,
+    enabled: bool,
+    #[serde(default)]
+    poll_interval_secs: Option<u64>,
+}
+
+// 重连退避和心跳参数；修改后从下一次连接（或下一次重连等待）起生效
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+struct ReconnectConfig {
+    base_ms: u64,
+    max_ms: u64,
+    jitter_pct: u64,
+    // 发送 Ping 的间隔
+    heartbeat_secs: u64,
+    // 超过这个时间没收到任何帧则判定连接失效
+    stale_secs: u64,
+    // 设置 TCP_NODELAY，行情帧不必等待合包
+    disable_nagle: bool,
+    // 单条消息和单帧的最大字节数，为空时使用 tungstenite 的默认值（64 MiB / 16 MiB）
+    max_message_bytes: Option<usize>,
+    max_frame_bytes: Option<usize>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        ReconnectConfig {
+            base_ms: 1_000,
+            max_ms: 60_000,
+            jitter_pct: 20,
+            heartbeat_secs: 20,
+            stale_secs: 45,
+            disable_nagle: true,
+            max_message_bytes: None,
+            max_frame_bytes: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.base_ms == 0 || self.max_ms == 0 || self.heartbeat_secs == 0 || self.stale_secs == 0 {
+            return Err(AppError::Validation("Reconnect and heartbeat values must be positive".to_string()));
+        }
+        if self.base_ms > self.max_ms {
+            return Err(AppError::Validation("Reconnect base delay must not exceed the maximum".to_string()));
+        }
+        if self.jitter_pct > 100 {
+            return Err(AppError::Validation("Reconnect jitter must be at most 100%".to_string()));
+        }
+        if self.stale_secs <= self.heartbeat_secs {
+            return Err(AppError::Validation("Stale timeout must be longer than the heartbeat interval".to_string()));
+        }
+        if self.max_message_bytes == Some(0) || self.max_frame_bytes == Some(0) {
+            return Err(AppError::Validation("Message and frame size limits must be positive".to_string()));
+        }
+        if let (Some(message), Some(frame)) = (self.max_message_bytes, self.max_frame_bytes) {
+            if frame > message {
+                return Err(AppError::Validation("Frame size limit must not exceed the message size limit".to_string()));
+            }
+        }
+        Ok(())
+    }
+    
+    fn ws_config(&self) -> WebSocketConfig {
+        let defaults = WebSocketConfig::default();
+        WebSocketConfig {
+            max_message_size: self.max_message_bytes.or(defaults.max_message_size),
+            max_frame_size: self.max_frame_bytes.or(defaults.max_frame_size),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+struct Settings {
+    version: u32,
+    platforms: Vec<PlatformConfig>,
+    bg_color: String,
+    text_color: String,
+    ws_url: String,
+    notifications_enabled: bool,
+    opacity: f64,
+    scale: f64,
+    window_x: Option<i32>,
+    window_y: Option<i32>,
+    proxy_url: Option<String>,
+    insecure_tls: bool,
+    log_max_bytes: u64,
+    log_level: LogLevel,
+    tray_icon_indicator: bool,
+    toggle_hotkey: Option<String>,
+    autostart: bool,
+    always_on_top: bool,
+    anchor_corner: String,
+    monitor_index: usize,
+    snap_margin: f64,
+    staleness_threshold_secs: u64,
+    usd_cny_rate: f64,
+    display_unit: DisplayUnit,
+    fx_endpoint: String,
+    fx_poll_interval_mins: u64,
+    spread_pairs: Vec<[String; 2]>,
+    history_enabled: bool,
+    dedup_ticks: bool,
+    max_emit_hz: f64,
+    simulate: bool,
+    simulate_interval_ms: u64,
+    simulate_seed: Option<u64>,
+    ws_binary_gzip: bool,
+    subscribe_action: String,
+    unsubscribe_action: String,
+    biz_type: String,
+    health_port: Option<u16>,
+    precision: HashMap<String, u8>,
+    layout_mode: String,
+    start_hidden: bool,
+    click_through: bool,
+    click_through_hotkey: Option<String>,
+    // 复制国际金价到剪贴板的全局快捷键
+    copy_price_hotkey: Option<String>,
+    user_resizable: bool,
+    window_w: Option<f64>,
+    window_h: Option<f64>,
+    // 设置后按该周期计算 EMA 并随行情推送
+    ema_period: Option<u32>,
+    // 告警触发时闪烁任务栏/程序坞，flash_reveal_window 为真时还会临时显示隐藏的窗口
+    flash_on_alert: bool,
+    flash_reveal_window: bool,
+    // 告警触发时播放提示音（alert_sound 为内置声音名称）
+    sound_on_alert: bool,
+    alert_sound: String,
+    // 托盘菜单语言（zh-CN / en）
+    language: String,
+    // 各品种的价格合理范围，超出的行情记录日志后丢弃
+    price_bounds: HashMap<String, PriceBounds>,
+    reconnect: ReconnectConfig,
+    // 告警记录保留天数，启动时清理，0 表示永久保留
+    alert_history_days: u32,
+    secondary_window: SecondaryWindowConfig,
+    // 每秒推送 freshness 事件，界面显示数据更新于几秒前
+    show_freshness: bool,
+    // 托盘提示文字模板，如 "{symbol} {bid} ({change})"
+    tooltip_template: String,
+    // 窗口隐藏时鼠标移到它所在的角落自动显示，移开后隐藏
+    hover_reveal: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        default_settings()
+    }
+}
+
+// 读多写少，用不会中毒的读写锁，某个处理函数 panic 后设置仍可用
+struct AppSettings(RwLock<Settings>);
+
+// 串行化设置的「读取-修改-保存」，托盘、快捷键和前端同时修改时不会丢失更新
+struct SettingsWriter(parking_lot::Mutex<()>);
+
+// 程序主动移动窗口的目标位置，用于区分用户拖动
+struct ProgrammaticMove(Mutex<Option<(i32, i32)>>);
+
+// 程序最近一次调整窗口大小的时间，之后短时间内的 Resized 事件不当作用户拖动
+struct ProgrammaticResize(Mutex<Option<std::time::Instant>>);
+const PROGRAMMATIC_RESIZE_GRACE_MS: u64 = 500;
+
+// 发送给 WebSocket 后台任务的控制指令
+enum WsCommand {
+    Reconnect,
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    // 调试用：原样发送一帧文本，结果通过 oneshot 回传
+    Send(String, oneshot::Sender<Result<(), AppError>>),
+}
+
+// 正在运行的 WebSocket 任务句柄
+struct WsTask {
+    commands: mpsc::UnboundedSender<WsCommand>,
+    shutdown: watch::Sender<bool>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+struct WsControl(Mutex<Option<WsTask>>);
+
+type WsStream = tokio_tungstenite::WebSocketStream<deflate::DeflateStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>;
+
+const SETTINGS_KEY: &str = "settings";
+// 设置的结构版本，修改已有字段的含义或形状时加一并在 upgrade_settings_value 中补充迁移
+const SETTINGS_VERSION: u32 = 2;
+const STORE_PATH: &str = "settings.bin";
+const DEFAULT_WS_URL: &str = "wss://cfws.jdjygold.com/data";
+const DEFAULT_SUBSCRIBE_ACTION: &str = "2";
+const DEFAULT_UNSUBSCRIBE_ACTION: &str = "3";
+const DEFAULT_BIZ_TYPE: &str = "2";
+const DEFAULT_FETCH_TIMEOUT_MS: u64 = 10_000;
+const FETCH_RETRY_BASE_MS: u64 = 300;
+const MIN_OPACITY: f64 = 0.1;
+const MAX_OPACITY: f64 = 1.0;
+const MIN_SCALE: f64 = 0.75;
+const MAX_SCALE: f64 = 2.0;
+// 布局模式及缩放为 1.0 时的窗口尺寸（宽、高）；compact 为原来的 280×40
+const LAYOUT_MODES: &[(&str, f64, f64)] = &[("compact", 280.0, 40.0), ("detailed", 420.0, 120.0)];
+const DEFAULT_LAYOUT_MODE: &str = "compact";
+const BASE_WINDOW_WIDTH: f64 = 280.0;
+const BASE_WINDOW_HEIGHT: f64 = 40.0;
+// 托盘菜单中的透明度预设（百分比）
+const OPACITY_PRESETS: &[u32] = &[100, 80, 60, 40];
+// 退出时等待 WebSocket 关闭的最长时间
+const SHUTDOWN_WS_TIMEOUT_MS: u64 = 2_000;
+
+// 国际金价的 WebSocket 行情代码
+const XAU_SYMBOL: &str = "WG-XAUUSD";
+// 旧版本设置中各平台的显示开关字段
+const LEGACY_PLATFORM_FIELDS: &[(&str, &str)] = &[
+    ("xau", "show_xau"),
+    ("ms", "show_ms"),
+    ("gh", "show_gh"),
+    ("zs", "show_zs"),
+];
+
+// 窗口吸附位置；Center 为原来的居中行为
+// 菜单文字见 i18n
+const ANCHOR_CORNERS: &[&str] = &["Center", "TopLeft", "TopRight", "BottomLeft", "BottomRight"];
+const DEFAULT_ANCHOR_CORNER: &str = "Center";
+// 与屏幕边缘的距离（逻辑像素），允许为负
+const DEFAULT_SNAP_MARGIN: f64 = 10.0;
+
+const TRAY_TOOLTIP: &str = "黄金价格监控";
+const TRAY_TOOLTIP_INTERVAL_MS: u64 = 1000;
+const TRAY_ICON_UP: &[u8] = include_bytes!("../icons/tray-up.png");
+const TRAY_ICON_DOWN: &[u8] = include_bytes!("../icons/tray-down.png");
+
+// 默认平台：WebSocket 推送的国际金价，加上后端轮询的各银行报价
+fn default_platforms() -> Vec<PlatformConfig> {
+    let xau = PlatformConfig {
+        id: "xau".to_string(),
+        label: "XAU".to_string(),
+        symbol: Some(XAU_SYMBOL.to_string()),
+        enabled: true,
+        poll_interval_secs: None,
+    };
+    std::iter::once(xau)
+        .chain(bank::BANK_SOURCES.iter().map(|source| PlatformConfig {
+            id: source.id.to_string(),
+            label: source.label.to_string(),
+            symbol: Some(source.symbol.to_string()),
+            enabled: true,
+            poll_interval_secs: Some(bank::DEFAULT_POLL_INTERVAL_SECS),
+        }))
+        .collect()
+}
+
+fn default_settings() -> Settings {
+    Settings {
+        version: SETTINGS_VERSION,
+        platforms: default_platforms(),
+        bg_color: "#2c3e50".to_string(),
+        text_color: colors::DEFAULT_TEXT_COLOR.to_string(),
+        ws_url: DEFAULT_WS_URL.to_string(),
+        notifications_enabled: true,
+        opacity: 1.0,
+        scale: 1.0,
+        window_x: None,
+        window_y: None,
+        proxy_url: None,
+        insecure_tls: true,
+        log_max_bytes: logging::DEFAULT_LOG_MAX_BYTES,
+        log_level: LogLevel::Info,
+        tray_icon_indicator: true,
+        toggle_hotkey: None,
+        autostart: false,
+        always_on_top: true,
+        anchor_corner: DEFAULT_ANCHOR_CORNER.to_string(),
+        monitor_index: 0,
+        snap_margin: DEFAULT_SNAP_MARGIN,
+        staleness_threshold_secs: staleness::DEFAULT_STALENESS_THRESHOLD_SECS,
+        usd_cny_rate: currency::DEFAULT_USD_CNY_RATE,
+        display_unit: DisplayUnit::Raw,
+        fx_endpoint: currency::DEFAULT_FX_ENDPOINT.to_string(),
+        fx_poll_interval_mins: currency::DEFAULT_FX_POLL_INTERVAL_MINS,
+        spread_pairs: Vec::new(),
+        history_enabled: false,
+        dedup_ticks: true,
+        max_emit_hz: 0.0,
+        simulate: false,
+        simulate_interval_ms: simulate::DEFAULT_SIMULATE_INTERVAL_MS,
+        simulate_seed: None,
+        ws_binary_gzip: true,
+        subscribe_action: DEFAULT_SUBSCRIBE_ACTION.to_string(),
+        unsubscribe_action: DEFAULT_UNSUBSCRIBE_ACTION.to_string(),
+        biz_type: DEFAULT_BIZ_TYPE.to_string(),
+        health_port: None,
+        precision: currency::default_precision(),
+        layout_mode: DEFAULT_LAYOUT_MODE.to_string(),
+        start_hidden: false,
+        click_through: false,
+        click_through_hotkey: None,
+        copy_price_hotkey: None,
+        user_resizable: false,
+        window_w: None,
+        window_h: None,
+        ema_period: None,
+        flash_on_alert: false,
+        flash_reveal_window: false,
+        sound_on_alert: false,
+        alert_sound: sound::DEFAULT_ALERT_SOUND.to_string(),
+        language: i18n::DEFAULT_LANGUAGE.to_string(),
+        price_bounds: HashMap::new(),
+        reconnect: ReconnectConfig::default(),
+        alert_history_days: history::DEFAULT_ALERT_HISTORY_DAYS,
+        secondary_window: SecondaryWindowConfig::default(),
+        show_freshness: false,
+        tooltip_template: tooltip::DEFAULT_TOOLTIP_TEMPLATE.to_string(),
+        hover_reveal: false,
+    }
+}
+
+fn validate_ws_url(ws_url: &str) -> Result<(), AppError> {
+    let parsed = url::Url::parse(ws_url).map_err(|e| AppError::Validation(format!("Invalid WebSocket URL '{ws_url}': {e}")))?;
+    match parsed.scheme() {
+        "ws" | "wss" => Ok(()),
+        scheme => Err(AppError::Validation(format!(
+            "Invalid WebSocket URL '{ws_url}': scheme must be ws:// or wss://, got {scheme}://"
+        ))),
+    }
+}
+
+fn validate_layout_mode(mode: &str) -> Result<(), AppError> {
+    if LAYOUT_MODES.iter().any(|(name, _, _)| *name == mode) {
+        Ok(())
+    } else {
+        let names: Vec<&str> = LAYOUT_MODES.iter().map(|(name, _, _)| *name).collect();
+        Err(AppError::Validation(format!("Invalid layout mode '{mode}': expected one of {}", names.join(", "))))
+    }
+}
+
+// 布局模式在缩放为 1.0 时的窗口尺寸，未知模式按 compact 处理
+fn layout_size(mode: &str) -> (f64, f64) {
+    LAYOUT_MODES
+        .iter()
+        .find(|(name, _, _)| *name == mode)
+        .map(|(_, width, height)| (*width, *height))
+        .unwrap_or((BASE_WINDOW_WIDTH, BASE_WINDOW_HEIGHT))
+}
+
+fn validate_anchor_corner(corner: &str) -> Result<(), AppError> {
+    if ANCHOR_CORNERS.contains(&corner) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("Invalid anchor corner '{corner}': expected one of {}", ANCHOR_CORNERS.join(", "))))
+    }
+}
+
+// 根据显示开关计算需要订阅的品种
+fn subscribed_symbols(settings: &Settings) -> Vec<String> {
+    settings
+        .platforms
+        .iter()
+        .filter(|platform| platform.enabled && bank::find_source(&platform.id).is_none())
+        .filter_map(|platform| platform.symbol.clone())
+        .collect()
+}
+
+// 把旧版本的四个 show_* 开关迁移为 platforms 列表，返回是否做了迁移
+fn migrate_legacy_platforms(value: &mut serde_json::Value) -> bool {
+    let Some(object) = value.as_object_mut() else {
+        return false;
+    };
+    let legacy: Vec<(&str, Option<bool>)> = LEGACY_PLATFORM_FIELDS
+        .iter()
+        .filter_map(|(id, field)| object.remove(*field).map(|enabled| (*id, enabled.as_bool())))
+        .collect();
+    if legacy.is_empty() || object.contains_key("platforms") {
+        return !legacy.is_empty();
+    }
+    let mut platforms = default_platforms();
+    for platform in &mut platforms {
+        if let Some((_, Some(enabled))) = legacy.iter().find(|(id, _)| *id == platform.id) {
+            platform.enabled = *enabled;
+        }
+    }
+    match serde_json::to_value(platforms) {
+        Ok(platforms) => {
+            object.insert("platforms".to_string(), platforms);
+        }
+        Err(e) => log(LogLevel::Error, &format!("migrate_legacy_platforms: encode failed: {}", e)),
+    }
+    true
+}
+
+// v1 的银行平台没有行情代码，补上代码和默认轮询间隔，返回是否做了修改
+fn migrate_bank_polling(value: &mut serde_json::Value) -> bool {
+    let Some(platforms) = value.get_mut("platforms").and_then(serde_json::Value::as_array_mut) else {
+        return false;
+    };
+    let mut changed = false;
+    for platform in platforms.iter_mut() {
+        let Some(source) = platform.get("id").and_then(serde_json::Value::as_str).and_then(bank::find_source) else {
+            continue;
+        };
+        let Some(object) = platform.as_object_mut() else {
+            continue;
+        };
+        if object.get("symbol").is_none_or(serde_json::Value::is_null) {
+            object.insert("symbol".to_string(), serde_json::json!(source.symbol));
+            changed = true;
+        }
+        if !object.contains_key("poll_interval_secs") {
+            object.insert("poll_interval_secs".to_string(), serde_json::json!(bank::DEFAULT_POLL_INTERVAL_SECS));
+            changed = true;
+        }
+    }
+    changed
+}
+
+// 保存的设置的版本，没有 version 字段的是 v0
+fn settings_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(0, |version| version as u32)
+}
+
+// 把设置 JSON 按版本逐步升级到当前版本，返回执行过的迁移
+fn upgrade_settings_value(value: &mut serde_json::Value) -> Vec<&'static str> {
+    let version = settings_version(value);
+    let mut applied = Vec::new();
+    if version < 1 && migrate_legacy_platforms(value) {
+        applied.push("v0 -> v1: show_* toggles to platforms");
+    }
+    if version < 2 && migrate_bank_polling(value) {
+        applied.push("v1 -> v2: bank platforms polled by the backend");
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), serde_json::json!(SETTINGS_VERSION));
+    }
+    applied
+}
+
+// 把任意版本保存的设置升级为当前结构：缺少的字段取默认值，无法解析的字段单独丢弃，其余保留
+fn migrate_settings(mut value: serde_json::Value) -> Settings {
+    for step in upgrade_settings_value(&mut value) {
+        log(LogLevel::Info, &format!("migrate_settings: {}", step));
+    }
+    let (Ok(mut merged), Some(stored)) = (serde_json::to_value(default_settings()), value.as_object()) else {
+        log(LogLevel::Error, "migrate_settings: saved settings are not an object, using defaults");
+        return default_settings();
+    };
+    for (key, field) in stored {
+        let Some(object) = merged.as_object_mut() else {
+            break;
+        };
+        if !object.contains_key(key) {
+            continue;
+        }
+        let previous = object.insert(key.clone(), field.clone());
+        if let Err(e) = serde_json::from_value::<Settings>(merged.clone()) {
+            log(LogLevel::Warn, &format!("migrate_settings: dropping invalid field '{}': {}", key, e));
+            if let (Some(object), Some(previous)) = (merged.as_object_mut(), previous) {
+                object.insert(key.clone(), previous);
+            }
+        }
+    }
+    serde_json::from_value(merged).unwrap_or_else(|e| {
+        log(LogLevel::Error, &format!("migrate_settings: decode failed: {}", e));
+        default_settings()
+    })
+}
+
+fn validate_platforms(platforms: &[PlatformConfig]) -> Result<(), AppError> {
+    for (index, platform) in platforms.iter().enumerate() {
+        if platform.id.trim().is_empty() {
+            return Err(AppError::Validation("Platform id must not be empty".to_string()));
+        }
+        if platforms[..index].iter().any(|other| other.id == platform.id) {
+            return Err(AppError::Validation(format!("Duplicate platform id '{}'", platform.id)));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SubscribeAction {
+    Subscribe,
+    Unsubscribe,
+}
+
+// 订阅/退订帧，action 和 bizType 取自设置
+#[derive(Serialize, Debug, Clone)]
+struct SubscribeConfig {
+    action: String,
+    #[serde(rename = "bizType")]
+    biz_type: String,
+    keys: Vec<String>,
+}
+
+impl SubscribeConfig {
+    fn from_settings(settings: &Settings, action: SubscribeAction, keys: &[String]) -> Result<Self, AppError> {
+        if keys.is_empty() {
+            return Err(AppError::Validation("Subscribe keys must not be empty".to_string()));
+        }
+        let action = match action {
+            SubscribeAction::Subscribe => &settings.subscribe_action,
+            SubscribeAction::Unsubscribe => &settings.unsubscribe_action,
+        };
+        Ok(SubscribeConfig {
+            action: action.clone(),
+            biz_type: settings.biz_type.clone(),
+            keys: keys.to_vec(),
+        })
+    }
+}
+
+fn subscribe_message<R: Runtime>(app: &AppHandle<R>, action: SubscribeAction, keys: &[String]) -> Result<String, AppError> {
+    let config = {
+        let state = app.state::<AppSettings>();
+        let guard = state.0.read();
+        SubscribeConfig::from_settings(&guard, action, keys)?
+    };
+    Ok(serde_json::to_string(&config)?)
+}
+
+// 计算第 n 次连续失败后的重连等待时间：指数增长、封顶并加入随机抖动
+fn reconnect_delay(config: &ReconnectConfig, failures: u32) -> tokio::time::Duration {
+    let base = config
+        .base_ms
+        .saturating_mul(1u64 << failures.min(16))
+        .min(config.max_ms);
+    let spread = base.saturating_mul(config.jitter_pct) / 100;
+    let delay = rand::thread_rng().gen_range(base - spread..=base + spread);
+    tokio::time::Duration::from_millis(delay)
+}
+
+// 通知正在运行的 WebSocket 任务
+fn send_ws_command<R: Runtime>(app: &AppHandle<R>, command: WsCommand) {
+    if let Ok(guard) = app.state::<WsControl>().0.lock() {
+        if let Some(task) = guard.as_ref() {
+            let _ = task.commands.send(command);
+        }
+    }
+}
+
+// 弹出系统通知（失败只记录日志）
+fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) {
+    if let Err(err) = app.notification().builder().title(title).body(body).show() {
+        log(LogLevel::Error, &format!("notify: show failed: {err}"));
+    }
+}
+
+// 获取设置
+#[tauri::command]
+fn get_settings<R: Runtime>(app: AppHandle<R>, _state: State<AppSettings>) -> Settings {
+    let store = match persist::store(&app) {
+        Ok(store) => store,
+        Err(err) => {
+            log(LogLevel::Error, &format!("get_settings: open store failed: {err}"));
+            persist::recover_corrupt_store(&app);
+            return default_settings();
+        }
+    };
+    match store.get(SETTINGS_KEY) {
+        Some(value) => migrate_settings(value),
+        None => default_settings(),
+    }
+}
+
+// 保存设置
+#[tauri::command]
+fn save_settings<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<AppSettings>,
+    settings: Settings,
+) -> Result<(), AppError> {
+    let writer = app.state::<SettingsWriter>();
+    let _writing = writer.0.lock();
+    apply_settings(app.clone(), state, settings)
+}
+
+// 在写入锁内基于最新设置修改并保存，避免并发修改互相覆盖；返回保存后的设置
+fn update_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    modify: impl FnOnce(&mut Settings) -> Result<(), AppError>,
+) -> Result<Settings, AppError> {
+    let writer = app.state::<SettingsWriter>();
+    let state = app.state::<AppSettings>();
+    modify_settings(&writer, &state.0, modify, |settings| {
+        apply_settings(app.clone(), app.state::<AppSettings>(), settings)
+    })
+}
+
+// 读取-修改-保存本身：持有写入锁时复制最新设置、修改后交给 apply 校验并写回，返回写回后的设置
+fn modify_settings(
+    writer: &SettingsWriter,
+    settings: &RwLock<Settings>,
+    modify: impl FnOnce(&mut Settings) -> Result<(), AppError>,
+    apply: impl FnOnce(Settings) -> Result<(), AppError>,
+) -> Result<Settings, AppError> {
+    let _writing = writer.0.lock();
+    let mut current = settings.read().clone();
+    modify(&mut current)?;
+    apply(current)?;
+    Ok(settings.read().clone())
+}
+
+// 校验并应用设置，调用方需持有 SettingsWriter
+fn apply_settings<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<AppSettings>,
+    settings: Settings,
+) -> Result<(), AppError> {
+    validate_ws_url(&settings.ws_url)?;
+    validate_anchor_corner(&settings.anchor_corner)?;
+    validate_layout_mode(&settings.layout_mode)?;
+    validate_platforms(&settings.platforms)?;
+    i18n::validate_language(&settings.language)?;
+    sound::validate_sound(&settings.alert_sound)?;
+    tooltip::validate_template(&settings.tooltip_template)?;
+    throttle::validate_max_emit_hz(settings.max_emit_hz)?;
+    settings.reconnect.validate()?;
+    for bounds in settings.price_bounds.values() {
+        bounds.validate()?;
+    }
+    if !settings.snap_margin.is_finite() {
+        return Err(AppError::Validation("Invalid snap margin".to_string()));
+    }
+    if settings.subscribe_action.trim().is_empty()
+        || settings.unsubscribe_action.trim().is_empty()
+        || settings.biz_type.trim().is_empty()
+    {
+        return Err(AppError::Validation("Subscribe action and biz type must not be empty".to_string()));
+    }
+    if settings.ema_period == Some(0) {
+        return Err(AppError::Validation("EMA period must be greater than 0".to_string()));
+    }
+    if settings.health_port == Some(0) {
+        return Err(AppError::Validation("Invalid health port 0".to_string()));
+    }
+    currency::validate_rate(settings.usd_cny_rate)?;
+    let mut settings = settings;
+    settings.opacity = settings.opacity.clamp(MIN_OPACITY, MAX_OPACITY);
+    settings.scale = settings.scale.clamp(MIN_SCALE, MAX_SCALE);
+    logging::set_log_max_bytes(settings.log_max_bytes);
+    logging::set_log_level(settings.log_level);
+    
+    // 开机自启和快捷键变化时先应用到系统，失败则不保存
+    let hotkeys: Vec<&str> = [&settings.toggle_hotkey, &settings.click_through_hotkey, &settings.copy_price_hotkey]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if (1..hotkeys.len()).any(|i| hotkeys[..i].contains(&hotkeys[i])) {
+        return Err(AppError::Validation("Toggle, click-through and copy-price hotkeys must differ".to_string()));
+    }
+    let (old_hotkeys, old_autostart, old_layout) = {
+        let guard = state.0.read();
+        (
+            [
+                guard.toggle_hotkey.clone(),
+                guard.click_through_hotkey.clone(),
+                guard.copy_price_hotkey.clone(),
+            ],
+            guard.autostart,
+            (guard.layout_mode.clone(), guard.scale),
+        )
+    };
+    // 布局或缩放变化时丢弃用户拖出的尺寸，按新预设调整
+    if old_layout != (settings.layout_mode.clone(), settings.scale) {
+        settings.window_w = None;
+        settings.window_h = None;
+    }
+    if old_autostart != settings.autostart {
+        apply_autostart(&app, settings.autostart)?;
+    }
+    let new_hotkeys = [&settings.toggle_hotkey, &settings.click_through_hotkey, &settings.copy_price_hotkey];
+    let hotkey_changes: Vec<(&Option<String>, &Option<String>)> = old_hotkeys.iter().zip(new_hotkeys).collect();
+    for (index, (old, new)) in hotkey_changes.iter().enumerate() {
+        if old == new {
+            continue;
+        }
+        if let Err(e) = hotkey::replace_hotkey(&app, old.as_deref(), new.as_deref()) {
+            // 撤销前面已生效的快捷键修改
+            for (old, new) in hotkey_changes[..index].iter().filter(|(old, new)| old != new) {
+                let _ = hotkey::replace_hotkey(&app, new.as_deref(), old.as_deref());
+            }
+            return Err(e);
+        }
+    }
+    
+    // 内存中的 store 立即更新，写盘走防抖，拖动滑块时不会每次都写磁盘
+    let store = persist::store(&app)?;
+    let value = serde_json::to_value(&settings)?;
+    store.set(SETTINGS_KEY, value);
+    persist::schedule_store_save(&app);
+    
+    let secondary_was_enabled = state.0.read().secondary_window.enabled;
+    let (url_changed, opacity_changed, scale_changed, layout_changed, on_top_changed, click_through_changed, margin_changed, old_symbols) = {
+        let mut guard = state.0.write();
+        let changed = guard.ws_url != settings.ws_url
+            || guard.proxy_url != settings.proxy_url
+            || guard.insecure_tls != settings.insecure_tls
+            || guard.subscribe_action != settings.subscribe_action
+            || guard.biz_type != settings.biz_type;
+        let opacity_changed = guard.opacity != settings.opacity;
+        let scale_changed = guard.scale != settings.scale
+            || guard.layout_mode != settings.layout_mode
+            || guard.user_resizable != settings.user_resizable;
+        let layout_changed = guard.layout_mode != settings.layout_mode;
+        let on_top_changed = guard.always_on_top != settings.always_on_top;
+        let click_through_changed = guard.click_through != settings.click_through;
+        let margin_changed = guard.snap_margin != settings.snap_margin;
+        let old_symbols = subscribed_symbols(&guard);
+        *guard = settings.clone();
+        (changed, opacity_changed, scale_changed, layout_changed, on_top_changed, click_through_changed, margin_changed, old_symbols)
+    };
+    
+    // 地址变更后让 WebSocket 重连到新地址
+    if url_changed {
+        log(LogLevel::Info, &format!("save_settings: connection settings changed, reconnecting to {}", settings.ws_url));
+        send_ws_command(&app, WsCommand::Reconnect);
+    }
+    
+    if opacity_changed {
+        apply_window_opacity(&app, settings.opacity);
+    }
+    if scale_changed {
+        apply_window_scale(&app, &settings.layout_mode, settings.scale);
+    }
+    if layout_changed {
+        log(LogLevel::Info, &format!("save_settings: layout mode {}", settings.layout_mode));
+        let _ = app.emit("layout-mode-changed", settings.layout_mode.clone());
+    }
+    if on_top_changed {
+        apply_always_on_top(&app, settings.always_on_top);
+    }
+    if click_through_changed {
+        apply_click_through(&app, settings.click_through);
+    }
+    // 边距变化且未手动摆放时重新吸附
+    if margin_changed && settings.window_x.is_none() {
+        position_window_anchored(&app);
+    }
+    if secondary_was_enabled != settings.secondary_window.enabled {
+        secondary::apply(&app, settings.secondary_window.enabled);
+    }
+    health::apply_health_port(&app, settings.health_port);
+    if !settings.tray_icon_indicator {
+        clear_tray_indicator(&app);
+    }
+    
+    // 显示开关变化时只增量调整订阅，不重连
+    let new_symbols = subscribed_symbols(&settings);
+    let added: Vec<String> = new_symbols.iter().filter(|s| !old_symbols.contains(s)).cloned().collect();
+    let removed: Vec<String> = old_symbols.iter().filter(|s| !new_symbols.contains(s)).cloned().collect();
+    if !removed.is_empty() {
+        send_ws_command(&app, WsCommand::Unsubscribe(removed));
+    }
+    if !added.is_empty() {
+        send_ws_command(&app, WsCommand::Subscribe(added));
+    }
+    
+    refresh_tray_menu(&app);
+    
+    // 通知前端设置已更新
+    let _ = app.emit("settings-updated", settings);
+    Ok(())
+}
+
+// 切换指定平台的显示状态
+#[tauri::command]
+fn toggle_platform<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        let platform = current
+            .platforms
+            .iter_mut()
+            .find(|platform| platform.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown platform '{id}'")))?;
+        platform.enabled = !platform.enabled;
+        Ok(())
+    })
+}
+
+// 设置背景颜色
+#[tauri::command]
+fn set_bg_color<R: Runtime>(
+    app: AppHandle<R>,
+    color: String,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.bg_color = colors::parse_color(&color)?;
+        Ok(())
+    })
+}
+
+// 应用主题（背景色、文字颜色、透明度一起保存）
+#[tauri::command]
+fn apply_theme<R: Runtime>(
+    app: AppHandle<R>,
+    name: String,
+) -> Result<Settings, AppError> {
+    let theme = colors::find_theme(&name).ok_or_else(|| AppError::NotFound(format!("Unknown theme '{name}'")))?;
+    update_settings(&app, |current| {
+        current.bg_color = theme.bg_color.to_string();
+        current.text_color = theme.text_color.to_string();
+        current.opacity = theme.opacity;
+        Ok(())
+    })
+}
+
+// 设置文字颜色
+#[tauri::command]
+fn set_text_color<R: Runtime>(
+    app: AppHandle<R>,
+    color: String,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.text_color = colors::parse_color(&color)?;
+        Ok(())
+    })
+}
+
+// 开启/关闭告警通知
+#[tauri::command]
+fn set_notifications_enabled<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.notifications_enabled = enabled;
+        Ok(())
+    })
+}
+
+// 设置窗口透明度（0.1 - 1.0）
+#[tauri::command]
+fn set_opacity<R: Runtime>(
+    app: AppHandle<R>,
+    opacity: f64,
+) -> Result<Settings, AppError> {
+    if !opacity.is_finite() {
+        return Err(AppError::Validation("Opacity must be a number".to_string()));
+    }
+    update_settings(&app, |current| {
+        current.opacity = opacity.clamp(MIN_OPACITY, MAX_OPACITY);
+        Ok(())
+    })
+}
+
+// 设置界面缩放（0.75 - 2.0），窗口按比例调整大小
+#[tauri::command]
+fn set_scale<R: Runtime>(
+    app: AppHandle<R>,
+    scale: f64,
+) -> Result<Settings, AppError> {
+    if !scale.is_finite() {
+        return Err(AppError::Validation("Scale must be a number".to_string()));
+    }
+    update_settings(&app, |current| {
+        current.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+        Ok(())
+    })
+}
+
+// 清除保存的窗口位置并重新居中
+#[tauri::command]
+fn reset_window_position<R: Runtime>(app: AppHandle<R>) -> Result<Settings, AppError> {
+    let current = update_settings(&app, |current| {
+        current.window_x = None;
+        current.window_y = None;
+        Ok(())
+    })?;
+    position_window_anchored(&app);
+    Ok(current)
+}
+
+// 「修复窗口」：尺寸恢复为布局预设，手动位置收回到所在显示器内，重新应用透明度和置顶并重新吸附；可重复调用
+#[tauri::command]
+fn normalize_window<R: Runtime>(app: AppHandle<R>) -> Result<Settings, AppError> {
+    let current = update_settings(&app, |current| {
+        current.window_w = None;
+        current.window_h = None;
+        Ok(())
+    })?;
+    apply_window_scale(&app, &current.layout_mode, current.scale);
+    
+    let mut current = current;
+    if let Some(window) = app.get_webview_window("main") {
+        if let Some((x, y)) = clamp_to_monitor(&window) {
+            log(LogLevel::Info, &format!("normalize_window: moved window back on screen at ({x}, {y})"));
+            move_window(&window, x, y);
+            if current.window_x.is_some() {
+                current = update_settings(&app, |current| {
+                    current.window_x = Some(x);
+                    current.window_y = Some(y);
+                    Ok(())
+                })?;
+            }
+        }
+    }
+    apply_window_opacity(&app, current.opacity);
+    apply_always_on_top(&app, current.always_on_top);
+    Ok(current)
+}
+
+// 窗口超出所在（或最近的）显示器时返回收回到显示器内的位置，已在显示器内时返回 None
+fn clamp_to_monitor<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Option<(i32, i32)> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| window.primary_monitor().ok().flatten())?;
+    let (left, top) = (monitor.position().x, monitor.position().y);
+    let right = left + monitor.size().width as i32 - size.width as i32;
+    let bottom = top + monitor.size().height as i32 - size.height as i32;
+    let x = position.x.clamp(left, right.max(left));
+    let y = position.y.clamp(top, bottom.max(top));
+    ((x, y) != (position.x, position.y)).then_some((x, y))
+}
+
+// 吸附到指定显示器的指定位置，并清除手动位置
+#[tauri::command]
+fn snap_window<R: Runtime>(
+    app: AppHandle<R>,
+    corner: Option<String>,
+    monitor_index: Option<usize>,
+) -> Result<Settings, AppError> {
+    let current = update_settings(&app, |current| {
+        if let Some(corner) = corner {
+            current.anchor_corner = corner;
+        }
+        if let Some(index) = monitor_index {
+            current.monitor_index = index;
+        }
+        current.window_x = None;
+        current.window_y = None;
+        Ok(())
+    })?;
+    position_window_anchored(&app);
+    Ok(current)
+}
+
+// 显示器信息，index 对应 monitor_index 设置
+#[derive(Serialize, Debug, Clone)]
+struct MonitorInfo {
+    index: usize,
+    name: Option<String>,
+    width: u32,
+    height: u32,
+    x: i32,
+    y: i32,
+    is_primary: bool,
+    scale_factor: f64,
+}
+
+// 列出可用显示器，供设置界面选择吸附的显示器；无显示器时返回空列表
+#[tauri::command]
+fn list_monitors<R: Runtime>(app: AppHandle<R>) -> Vec<MonitorInfo> {
+    let monitors = match app.available_monitors() {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            log(LogLevel::Warn, &format!("list_monitors: {}", e));
+            return Vec::new();
+        }
+    };
+    let primary = app.primary_monitor().ok().flatten();
+    monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index,
+            name: monitor.name().cloned(),
+            width: monitor.size().width,
+            height: monitor.size().height,
+            x: monitor.position().x,
+            y: monitor.position().y,
+            is_primary: primary.as_ref().is_some_and(|primary| {
+                primary.position() == monitor.position() && primary.size() == monitor.size()
+            }),
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect()
+}
+
+// 设置显示/隐藏窗口的全局快捷键，传 None 取消
+#[tauri::command]
+fn set_hotkey<R: Runtime>(
+    app: AppHandle<R>,
+    hotkey: Option<String>,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.toggle_hotkey = hotkey.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+        Ok(())
+    })
+}
+
+// 设置切换鼠标穿透的全局快捷键，传 None 取消
+#[tauri::command]
+fn set_click_through_hotkey<R: Runtime>(
+    app: AppHandle<R>,
+    hotkey: Option<String>,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.click_through_hotkey = hotkey.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+        Ok(())
+    })
+}
+
+// 设置复制国际金价的全局快捷键，传 None 取消
+#[tauri::command]
+fn set_copy_price_hotkey<R: Runtime>(
+    app: AppHandle<R>,
+    hotkey: Option<String>,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.copy_price_hotkey = hotkey.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+        Ok(())
+    })
+}
+
+// 设置托盘提示文字模板，占位符不认识时报错
+#[tauri::command]
+fn set_tooltip_template<R: Runtime>(
+    app: AppHandle<R>,
+    template: String,
+) -> Result<Settings, AppError> {
+    tooltip::validate_template(&template)?;
+    update_settings(&app, |current| {
+        current.tooltip_template = template;
+        Ok(())
+    })
+}
+
+// 更新美元兑人民币汇率（用于 XAU 折算人民币/克）
+#[tauri::command]
+fn set_usd_cny_rate<R: Runtime>(
+    app: AppHandle<R>,
+    rate: f64,
+) -> Result<Settings, AppError> {
+    currency::validate_rate(rate)?;
+    update_settings(&app, |current| {
+        current.usd_cny_rate = rate;
+        Ok(())
+    })
+}
+
+// 设置某个品种显示的小数位数
+#[tauri::command]
+fn set_precision<R: Runtime>(
+    app: AppHandle<R>,
+    symbol: String,
+    digits: u8,
+) -> Result<Settings, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::Validation("Symbol must not be empty".to_string()));
+    }
+    if digits > currency::MAX_PRECISION {
+        return Err(AppError::Validation(format!("Precision must be at most {} digits", currency::MAX_PRECISION)));
+    }
+    update_settings(&app, |current| {
+        current.precision.insert(symbol, digits);
+        Ok(())
+    })
+}
+
+// 调整重连退避和心跳参数，下一次连接周期生效，无需重启
+#[tauri::command]
+fn set_reconnect_config<R: Runtime>(
+    app: AppHandle<R>,
+    config: ReconnectConfig,
+) -> Result<Settings, AppError> {
+    config.validate()?;
+    update_settings(&app, |current| {
+        current.reconnect = config;
+        Ok(())
+    })
+}
+
+// 设置某个品种的价格合理范围，上下限都为空时移除
+#[tauri::command]
+fn set_price_bounds<R: Runtime>(
+    app: AppHandle<R>,
+    symbol: String,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+) -> Result<Settings, AppError> {
+    let symbol = symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(AppError::Validation("Symbol must not be empty".to_string()));
+    }
+    let bounds = PriceBounds { min_price, max_price };
+    bounds.validate()?;
+    update_settings(&app, |current| {
+        if bounds == PriceBounds::default() {
+            current.price_bounds.remove(&symbol);
+        } else {
+            current.price_bounds.insert(symbol, bounds);
+        }
+        Ok(())
+    })
+}
+
+// 切换布局模式（compact / detailed），窗口随之调整大小
+#[tauri::command]
+fn set_layout_mode<R: Runtime>(app: AppHandle<R>, mode: String) -> Result<Settings, AppError> {
+    validate_layout_mode(&mode)?;
+    update_settings(&app, |current| {
+        current.layout_mode = mode;
+        Ok(())
+    })
+}
+
+// 开启/关闭鼠标穿透
+#[tauri::command]
+fn set_click_through<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.click_through = enabled;
+        Ok(())
+    })
+}
+
+// 设置窗口是否置顶
+#[tauri::command]
+fn set_always_on_top<R: Runtime>(
+    app: AppHandle<R>,
+    always_on_top: bool,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.always_on_top = always_on_top;
+        Ok(())
+    })
+}
+
+// 开启/关闭开机自启
+#[tauri::command]
+fn set_autostart<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.autostart = enabled;
+        Ok(())
+    })
+}
+
+// 写入或删除系统的开机自启项
+fn apply_autostart<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), AppError> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled { autolaunch.enable() } else { autolaunch.disable() };
+    result.map_err(|e| {
+        log(LogLevel::Error, &format!("apply_autostart: {}", e));
+        AppError::Platform(format!("Failed to update autostart: {e}"))
+    })
+}
+
+// 启动时以系统实际状态为准，修正保存的开关（用户可能在系统设置里改过）
+fn reconcile_autostart<R: Runtime>(app: &AppHandle<R>) {
+    let actual = match app.autolaunch().is_enabled() {
+        Ok(actual) => actual,
+        Err(e) => {
+            log(LogLevel::Warn, &format!("reconcile_autostart: query failed: {}", e));
+            return;
+        }
+    };
+    let settings = {
+        let state = app.state::<AppSettings>();
+        let mut guard = state.0.write();
+        if guard.autostart == actual {
+            return;
+        }
+        log(LogLevel::Warn, &format!(
+            "reconcile_autostart: stored flag {} but system entry is {}, using system state",
+            guard.autostart, actual
+        ));
+        guard.autostart = actual;
+        guard.clone()
+    };
+    match (persist::store(app), serde_json::to_value(&settings)) {
+        (Ok(store), Ok(value)) => {
+            store.set(SETTINGS_KEY, value);
+            persist::schedule_store_save(app);
+        }
+        (Err(e), _) => log(LogLevel::Error, &format!("reconcile_autostart: open store failed: {}", e)),
+        (_, Err(e)) => log(LogLevel::Error, &format!("reconcile_autostart: encode failed: {}", e)),
+    }
+}
+
+// 恢复默认设置
+#[tauri::command]
+fn reset_settings<R: Runtime>(app: AppHandle<R>, state: State<AppSettings>) -> Result<Settings, AppError> {
+    let defaults = default_settings();
+    save_settings(app.clone(), state, defaults.clone())?;
+    // 透明度和缩放已在 save_settings 中按变化重新应用，这里把窗口放回默认位置
+    place_window(&app);
+    log(LogLevel::Info, "reset_settings: restored defaults");
+    Ok(defaults)
+}
+
+// 导出设置为 JSON 字符串
+#[tauri::command]
+fn export_settings(state: State<AppSettings>) -> Result<String, AppError> {
+    let current = state.0.read().clone();
+    Ok(serde_json::to_string_pretty(&current)?)
+}
+
+// 从 JSON 字符串导入设置
+#[tauri::command]
+fn import_settings<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<AppSettings>,
+    json: String,
+) -> Result<Settings, AppError> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| AppError::Validation(format!("Invalid settings JSON: {e}")))?;
+    upgrade_settings_value(&mut value);
+    let object = value
+        .as_object()
+        .ok_or_else(|| AppError::Validation("Invalid settings JSON: expected an object".to_string()))?;
+    let known = serde_json::to_value(default_settings())?;
+    if let Some(unknown) = object.keys().find(|key| known.get(key.as_str()).is_none()) {
+        return Err(AppError::Validation(format!("Invalid settings JSON: unknown field '{unknown}'")));
+    }
+    let mut settings: Settings =
+        serde_json::from_value(value).map_err(|e| AppError::Validation(format!("Invalid settings JSON: {e}")))?;
+    settings.bg_color = colors::parse_color(&settings.bg_color)?;
+    settings.text_color = colors::parse_color(&settings.text_color)?;
+    
+    save_settings(app.clone(), state, settings.clone())?;
+    log(LogLevel::Info, "import_settings: settings imported");
+    Ok(settings)
+}
+
+// 开启/关闭跳过证书验证
+#[tauri::command]
+fn set_insecure_tls<R: Runtime>(
+    app: AppHandle<R>,
+    insecure: bool,
+) -> Result<Settings, AppError> {
+    update_settings(&app, |current| {
+        current.insecure_tls = insecure;
+        Ok(())
+    })
+}
+
+// 退出应用；清理在 RunEvent::Exit 中统一进行
+#[tauri::command]
+fn quit_app<R: Runtime>(app: AppHandle<R>) {
+    app.exit(0);
+}
+
+// 退出前的清理：停止 WebSocket、关闭历史数据库（防抖中的设置由 persist 插件在 Exit 时写入）
+fn graceful_shutdown<R: Runtime>(app: &AppHandle<R>) {
+    log(LogLevel::Info, "shutdown: cleaning up");
+    let stopped = tauri::async_runtime::block_on(tokio::time::timeout(
+        tokio::time::Duration::from_millis(SHUTDOWN_WS_TIMEOUT_MS),
+        stop_websocket(app.clone()),
+    ));
+    match stopped {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log(LogLevel::Warn, &format!("shutdown: stop websocket failed: {}", e)),
+        Err(_) => log(LogLevel::Warn, "shutdown: websocket did not close in time"),
+    }
+    history::close(app);
+    log(LogLevel::Info, "shutdown: clean shutdown complete");
+}
+
+// fetch_with_no_ssl 的返回值，前端收到 { status, headers, body }
+#[derive(Serialize, Debug)]
+struct FetchResponse {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+// 按当前设置（代理、insecure_tls）创建 HTTP 客户端
+fn http_client<R: Runtime>(app: &AppHandle<R>, timeout_ms: u64) -> Result<reqwest::Client, AppError> {
+    let insecure_tls = current_insecure_tls(app);
+    let mut builder = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(timeout_ms))
+        .danger_accept_invalid_certs(insecure_tls)
+        .danger_accept_invalid_hostnames(insecure_tls);
+    if let Some(proxy_url) = current_proxy(app) {
+        match reqwest::Proxy::all(proxy_url.as_str()) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => log(LogLevel::Warn, &format!("http_client: proxy error: {}, using direct connection", e)),
+        }
+    }
+    builder.build().map_err(|e| {
+        log(LogLevel::Error, &format!("http_client: client build error: {}", e));
+        AppError::from(e)
+    })
+}
+
+// 按方法名构造请求，GET 以外的方法带上 JSON 请求体
+fn build_request(
+    client: &reqwest::Client,
+    method: &str,
+    url: &str,
+    body: Option<String>,
+) -> Result<reqwest::RequestBuilder, AppError> {
+    let request = match method.to_uppercase().as_str() {
+        "GET" => client.get(url),
+        verb @ ("POST" | "PUT" | "PATCH" | "DELETE") => {
+            let http_method = match verb {
+                "POST" => reqwest::Method::POST,
+                "PUT" => reqwest::Method::PUT,
+                "PATCH" => reqwest::Method::PATCH,
+                _ => reqwest::Method::DELETE,
+            };
+            let mut req = client.request(http_method, url);
+            if let Some(body_str) = body {
+                req = req.header("Content-Type", "application/json").body(body_str);
+            }
+            req
+        },
+        _ => return Err(AppError::Validation(format!("Unsupported method: {}", method))),
+    };
+    Ok(request)
+}
+
+// 自定义 HTTP 请求（insecure_tls 开启时跳过 SSL 验证）
+#[tauri::command]
+async fn fetch_with_no_ssl<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    method: String,
+    body: Option<String>,
+    timeout_ms: Option<u64>,
+    retries: Option<u32>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<FetchResponse, AppError> {
+    let retries = retries.unwrap_or(0);
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_FETCH_TIMEOUT_MS);
+    log(LogLevel::Info, &format!("fetch_with_no_ssl: {} {} (timeout {}ms)", method, url, timeout_ms));
+    
+    let client = http_client(&app, timeout_ms)?;
+    
+    let request = build_request(&client, &method, &url, body)?;
+    let request = match headers {
+        Some(headers) => request.headers(build_header_map(&headers)?),
+        None => request,
+    };
+    
+    // 连接错误、超时和 5xx 视为临时故障可重试，4xx 不重试
+    let mut attempt: u32 = 0;
+    let response = loop {
+        let Some(pending) = request.try_clone() else {
+            return Err(AppError::Internal("Request cannot be retried".to_string()));
+        };
+        let retryable = attempt < retries;
+        match pending.send().await {
+            Ok(response) if response.status().is_server_error() && retryable => {
+                log(LogLevel::Info, &format!("fetch_with_no_ssl: status {}", response.status()));
+            }
+            Ok(response) => break response,
+            Err(e) if retryable && (e.is_timeout() || e.is_connect() || e.is_request()) => {
+                log(LogLevel::Error, &format!("fetch_with_no_ssl: request error: {}", e));
+            }
+            Err(e) => {
+                log(LogLevel::Error, &format!("fetch_with_no_ssl: request error: {}", e));
+                return Err(fetch_error_message(&e));
+            }
+        }
+        attempt += 1;
+        log(LogLevel::Warn, &format!("fetch_with_no_ssl: retry {}/{}", attempt, retries));
+        tokio::time::sleep(fetch_retry_delay(attempt)).await;
+    };
+    
+    let status = response.status();
+    log(LogLevel::Info, &format!("fetch_with_no_ssl: status {}", status));
+    // 同名响应头合并为逗号分隔
+    let mut response_headers: HashMap<String, String> = HashMap::new();
+    for (name, value) in response.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        response_headers
+            .entry(name.as_str().to_string())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+    
+    let text = response.text().await.map_err(|e| {
+        log(LogLevel::Error, &format!("fetch_with_no_ssl: read body error: {}", e));
+        fetch_error_message(&e)
+    })?;
+    
+    log(LogLevel::Debug, &format!("fetch_with_no_ssl: response body: {}", logging::truncate_utf8(&text, 200)));
+    
+    Ok(FetchResponse {
+        status: status.as_u16(),
+        headers: response_headers,
+        body: text,
+    })
+}
+
+fn build_header_map(headers: &HashMap<String, String>) -> Result<reqwest::header::HeaderMap, AppError> {
+    let mut map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| AppError::Validation(format!("Invalid header name '{}': {}", name, e)))?;
+        let header_value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|e| AppError::Validation(format!("Invalid header value for '{}': {}", name, e)))?;
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+fn fetch_retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(FETCH_RETRY_BASE_MS << attempt.saturating_sub(1).min(5))
+}
+
+// 超时单独返回固定文案，方便前端提示重试；证书校验失败时提示可关闭校验
+fn fetch_error_message(err: &reqwest::Error) -> AppError {
+    if err.is_timeout() {
+        return AppError::Network("request timed out".to_string());
+    }
+    if is_certificate_error(err) {
+        return AppError::Network(format!(
+            "TLS certificate verification failed ({}); enable insecure_tls to skip verification",
+            err
+        ));
+    }
+    AppError::Network(err.to_string())
+}
+
+fn is_certificate_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(current) = source {
+        if current.to_string().to_lowercase().contains("certificate") {
+            return true;
+        }
+        source = current.source();
+    }
+    false
+}
+
+// 启动 WebSocket 客户端（在 Rust 后端）
+#[tauri::command]
+async fn start_websocket<R: Runtime>(
+    app: AppHandle<R>,
+    symbols: Option<Vec<String>>,
+) -> Result<(), AppError> {
+    log(LogLevel::Info, "start_websocket: begin");
+    
+    let ws_url = current_ws_url(&app);
+    validate_ws_url(&ws_url).map_err(|e| {
+        log(LogLevel::Error, &format!("start_websocket: {}", e));
+        e
+    })?;
+    let (keys, simulation) = {
+        let state = app.state::<AppSettings>();
+        let guard = state.0.read();
+        (
+            symbols.unwrap_or_else(|| subscribed_symbols(&guard)),
+            guard
+                .simulate
+                .then_some((guard.simulate_interval_ms, guard.simulate_seed)),
+        )
+    };
+    
+    let control = app.state::<WsControl>();
+    let mut guard = match control.0.lock() {
+        Ok(guard) => guard,
+        Err(_) => {
+            log(LogLevel::Error, "start_websocket: ws control lock poisoned");
+            return Err(AppError::LockPoisoned("WebSocket control"));
+        }
+    };
+    // 已有任务在运行时直接返回，避免重复建立连接
+    if guard.as_ref().is_some_and(|task| !task.handle.is_finished()) {
+        log(LogLevel::Info, "start_websocket: already running");
+        return Ok(());
+    }
+    
+    log(LogLevel::Info, &format!("start_websocket: symbols {:?}", keys));
+    let (commands, commands_rx) = mpsc::unbounded_channel();
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    // simulate 开启时用模拟行情代替真实连接（在下次启动时生效）
+    let handle = match simulation {
+        Some((interval_ms, seed)) => tokio::spawn(simulate::run_simulation(
+            app.clone(),
+            keys,
+            interval_ms,
+            seed,
+            commands_rx,
+            shutdown_rx,
+        )),
+        None => tokio::spawn(run_websocket(app.clone(), keys, commands_rx, shutdown_rx)),
+    };
+    *guard = Some(WsTask {
+        commands,
+        shutdown,
+        handle,
+    });
+    
+    Ok(())
+}
+
+// 立即断开并重连 WebSocket，同时清零退避计数；未运行时直接返回
+#[tauri::command]
+fn reconnect_websocket<R: Runtime>(app: AppHandle<R>) -> Result<(), AppError> {
+    log(LogLevel::Info, "reconnect_websocket: requested");
+    send_ws_command(&app, WsCommand::Reconnect);
+    Ok(())
+}
+
+// 通过当前连接发送任意文本帧，用于调试协议；未连接时返回错误
+#[tauri::command]
+async fn send_ws_message<R: Runtime>(app: AppHandle<R>, text: String) -> Result<(), AppError> {
+    let (reply, result) = oneshot::channel();
+    {
+        let control = app.state::<WsControl>();
+        let guard = control.0.lock().map_err(|_| AppError::LockPoisoned("WebSocket control"))?;
+        let task = guard.as_ref().ok_or(AppError::NotConnected)?;
+        task.commands
+            .send(WsCommand::Send(text, reply))
+            .map_err(|_| AppError::NotConnected)?;
+    }
+    result
+        .await
+        .map_err(|_| AppError::Internal("WebSocket task dropped the message".to_string()))?
+}
+
+// 停止 WebSocket 客户端，等待连接关闭后返回
+#[tauri::command]
+async fn stop_websocket<R: Runtime>(app: AppHandle<R>) -> Result<(), AppError> {
+    let task = match app.state::<WsControl>().0.lock() {
+        Ok(mut guard) => guard.take(),
+        Err(_) => {
+            log(LogLevel::Error, "stop_websocket: ws control lock poisoned");
+            return Err(AppError::LockPoisoned("WebSocket control"));
+        }
+    };
+    let Some(task) = task else {
+        log(LogLevel::Warn, "stop_websocket: not running");
+        return Ok(());
+    };
+    
+    let _ = task.shutdown.send(true);
+    if let Err(e) = task.handle.await {
+        log(LogLevel::Error, &format!("stop_websocket: task join error: {}", e));
+        return Err(AppError::Internal(e.to_string()));
+    }
+    log(LogLevel::Info, "stop_websocket: stopped");
+    Ok(())
+}
+
+// 连接状态事件，attempt 为当前连续失败次数
+#[derive(Serialize, Clone, Debug)]
+struct WsStatus {
+    state: &'static str,
+    detail: Option<String>,
+    attempt: u32,
+}
+
+fn emit_ws_status<R: Runtime>(app: &AppHandle<R>, state: &'static str, detail: Option<String>, attempt: u32) {
+    let _ = app.emit("ws-status", WsStatus { state, detail, attempt });
+}
+
+// WebSocket 后台任务：连接、订阅、接收，断开后自动重连，收到停止信号时退出
+async fn run_websocket<R: Runtime>(
+    app: AppHandle<R>,
+    mut keys: Vec<String>,
+    mut commands: mpsc::UnboundedReceiver<WsCommand>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    // 连续失败次数，收到消息后清零
+    let mut failures: u32 = 0;
+    'reconnect: while !*shutdown.borrow() {
+        // 没有订阅品种时不连接，等待订阅指令
+        if keys.is_empty() {
+            log(LogLevel::Warn, "start_websocket: no symbols subscribed, waiting before connecting");
+            emit_ws_status(&app, "disconnected", Some("no symbols subscribed".to_string()), failures);
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(WsCommand::Subscribe(added)) => {
+                        for key in added {
+                            if !keys.contains(&key) {
+                                keys.push(key);
+                            }
+                        }
+                    }
+                    Some(WsCommand::Unsubscribe(_)) | Some(WsCommand::Reconnect) => {}
+                    Some(WsCommand::Send(_, reply)) => {
+                        let _ = reply.send(Err(AppError::NotConnected));
+                    }
+                    None => break 'reconnect,
+                },
+                _ = shutdown.changed() => break 'reconnect,
+            }
+            continue;
+        }
+        
+        // 每次连接前重新读取地址，以便设置变更后生效
+        let ws_url = current_ws_url(&app);
+        log(LogLevel::Info, &format!("start_websocket: connecting to {}...", ws_url));
+        emit_ws_status(&app, "connecting", Some(ws_url.clone()), failures);
+        
+        // 使用 native-tls 连接器，insecure_tls 开启时跳过证书验证
+        let insecure_tls = current_insecure_tls(&app);
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(insecure_tls)
+            .build()
+            .map(tokio_tungstenite::Connector::NativeTls);
+        
+        let result = match connector {
+            Ok(connector) => {
+                let proxy = current_proxy(&app);
+                let (ws_config, disable_nagle) = current_ws_config(&app);
+                let connect = connect_async_with_config(
+                    &ws_url,
+                    Some(ws_config),
+                    disable_nagle,
+                    Some(connector),
+                    proxy.as_ref(),
+                );
+                tokio::select! {
+                    result = connect => result,
+                    _ = shutdown.changed() => break,
+                }
+            }
+            Err(e) => Err(tokio_tungstenite::tungstenite::Error::Tls(e.into())),
+        };
+        
+        match result {
+            Ok((mut ws_stream, _)) => {
+                log(LogLevel::Info, "start_websocket: connected");
+                emit_ws_status(&app, "connected", None, failures);
+                stats::on_connected(&app);
+                let end = run_session(&app, &mut ws_stream, &mut keys, &mut failures, &mut commands, &mut shutdown).await;
+                stats::on_disconnected(&app);
+                match end {
+                    SessionEnd::Disconnected => {
+                        emit_ws_status(&app, "disconnected", Some("connection lost".to_string()), failures);
+                    }
+                    SessionEnd::Reconnect => {
+                        emit_ws_status(&app, "disconnected", Some("reconnect requested".to_string()), failures);
+                        continue;
+                    }
+                    SessionEnd::Stop => {
+                        let _ = ws_stream.close(None).await;
+                        break;
+                    }
+                }
+            }
+            Err(e) => {
+                if is_certificate_error(&e) {
+                    log(LogLevel::Error, &format!(
+                        "start_websocket: TLS certificate verification failed ({}); enable insecure_tls to skip verification",
+                        e
+                    ));
+                } else {
+                    log(LogLevel::Error, &format!("start_websocket: connect error: {}", e));
+                }
+                emit_ws_status(&app, "error", Some(e.to_string()), failures);
+            }
+        }
+        
+        let delay = {
+            let state = app.state::<AppSettings>();
+            let config = &state.0.read().reconnect;
+            reconnect_delay(config, failures)
+        };
+        failures = failures.saturating_add(1);
+        log(LogLevel::Info, &format!(
+            "start_websocket: reconnecting in {}ms (attempt {})...",
+            delay.as_millis(),
+            failures
+        ));
+        let deadline = tokio::time::Instant::now() + delay;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                command = commands.recv() => match command {
+                    Some(WsCommand::Reconnect) => {
+                        log(LogLevel::Info, "start_websocket: reconnect requested");
+                        failures = 0;
+                        break;
+                    }
+                    // 未连接时只更新订阅列表，连上后统一订阅
+                    Some(WsCommand::Subscribe(added)) => {
+                        for key in added {
+                            if !keys.contains(&key) {
+                                keys.push(key);
+                            }
+                        }
+                    }
+                    Some(WsCommand::Unsubscribe(removed)) => keys.retain(|k| !removed.contains(k)),
+                    Some(WsCommand::Send(_, reply)) => {
+                        let _ = reply.send(Err(AppError::NotConnected));
+                    }
+                    None => break 'reconnect,
+                },
+                _ = shutdown.changed() => break 'reconnect,
+            }
+        }
+    }
+    log(LogLevel::Info, "start_websocket: task exited");
+    emit_ws_status(&app, "disconnected", Some("stopped".to_string()), failures);
+}
+
+fn handle_text_frame<R: Runtime>(app: &AppHandle<R>, text: &str, keys: &[String]) {
+    log(LogLevel::Debug, &format!("start_websocket: received: {}", logging::truncate_utf8(text, 100)));
+    tick::record_raw(app, text);
+    match PriceTick::from_ws_json(text, keys) {
+        Ok(tick) => {
+            tick::assign_raw(app, &tick.symbol);
+            stats::on_tick(app);
+            handle_tick(app, tick);
+        }
+        Err(e) => log(LogLevel::Warn, &format!("start_websocket: dropped malformed frame: {}", e)),
+    }
+}
+
+// 二进制帧按 UTF-8 解码；开启 ws_binary_gzip 且带 gzip 头时先解压
+fn decode_binary_frame(data: &[u8], gzip: bool) -> Result<String, String> {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+    if gzip && data.starts_with(&GZIP_MAGIC) {
+        use std::io::Read;
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(data)
+            .read_to_string(&mut text)
+            .map_err(|e| format!("gunzip failed: {e}"))?;
+        return Ok(text);
+    }
+    String::from_utf8(data.to_vec()).map_err(|e| format!("not UTF-8: {e}"))
+}
+
+// 处理一条解析后的行情：丢弃超出范围和乱序的、去重、记录、检查告警并推送给前端（暂停时不推送）
+fn handle_tick<R: Runtime>(app: &AppHandle<R>, tick: PriceTick) {
+    let out_of_bounds = app
+        .state::<AppSettings>()
+        .0
+        .read()
+        .price_bounds
+        .get(&tick.symbol)
+        .is_some_and(|bounds| !bounds.contains(tick.bid));
+    if out_of_bounds {
+        log(LogLevel::Warn, &format!("handle_tick: dropped {} at {} outside price bounds", tick.symbol, tick.bid));
+        return;
+    }
+    if tick::is_out_of_order(app, &tick) {
+        stats::on_out_of_order(app);
+        return;
+    }
+    staleness::mark_fresh(app, &tick.symbol);
+    let dedup = app.state::<AppSettings>().0.read().dedup_ticks;
+    if dedup && tick::is_duplicate(app, &tick) {
+        return;
+    }
+    
+    tick::record_tick(app, &tick);
+    ema::update(app, &tick);
+    daily::record(app, &tick);
+    history::record(app, &tick);
+    spread::notify_tick(app, &tick.symbol);
+    alerts::check_tick(app, &tick);
+    if pause::is_paused(app) {
+        return;
+    }
+    update_tray(app, &tick);
+    throttle::emit_price(app, tick);
+}
+
+// 按显示单位换算、按精度格式化后推送 price-update 到显示该品种的窗口
+fn emit_price_update<R: Runtime>(app: &AppHandle<R>, tick: PriceTick) {
+    let ema = ema::current(app, &tick.symbol);
+    let (display, target) = {
+        let state = app.state::<AppSettings>();
+        let settings = state.0.read();
+        (
+            currency::format_tick(&settings, &tick, ema),
+            secondary::window_for_symbol(&settings, &tick.symbol),
+        )
+    };
+    let _ = app.emit_to(target, "price-update", display);
+}
+
+// 单次连接的结束原因
+enum SessionEnd {
+    Disconnected,
+    Reconnect,
+    Stop,
+}
+
+// 在已建立的连接上订阅并接收消息，直到断开或收到控制指令
+async fn run_session<R: Runtime>(
+    app: &AppHandle<R>,
+    ws_stream: &mut WsStream,
+    keys: &mut Vec<String>,
+    failures: &mut u32,
+    commands: &mut mpsc::UnboundedReceiver<WsCommand>,
+    shutdown: &mut watch::Receiver<bool>,
+) -> SessionEnd {
+    // 发送订阅消息
+    if !keys.is_empty() {
+        let subscribe_msg = match subscribe_message(app, SubscribeAction::Subscribe, keys) {
+            Ok(msg) => msg,
+            Err(e) => {
+                log(LogLevel::Error, &format!("start_websocket: build subscribe failed: {}", e));
+                return SessionEnd::Disconnected;
+            }
+        };
+        if let Err(e) = ws_stream.send(Message::Text(subscribe_msg)).await {
+            log(LogLevel::Error, &format!("start_websocket: send error: {}", e));
+            return SessionEnd::Disconnected;
+        }
+        log(LogLevel::Info, &format!("start_websocket: subscribed {:?}", keys));
+    }
+    
+    // 心跳：定时发送 Ping，超时未收到任何帧则判定连接已失效
+    let (heartbeat_secs, stale_secs) = {
+        let state = app.state::<AppSettings>();
+        let config = &state.0.read().reconnect;
+        (config.heartbeat_secs, config.stale_secs)
+    };
+    let heartbeat = tokio::time::Duration::from_secs(heartbeat_secs);
+    let heartbeat_timeout = tokio::time::Duration::from_secs(stale_secs);
+    let mut ping_ticker = tokio::time::interval_at(tokio::time::Instant::now() + heartbeat, heartbeat);
+    let mut last_seen = tokio::time::Instant::now();
+    
+    // 接收消息
+    loop {
+        tokio::select! {
+            msg = ws_stream.next() => {
+                if let Some(Ok(_)) = &msg {
+                    last_seen = tokio::time::Instant::now();
+                    stats::on_message(app, ws_stream.get_ref().traffic());
+                }
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        *failures = 0;
+                        handle_text_frame(app, &text, keys);
+                    }
+                    Some(Ok(Message::Binary(data))) => {
+                        *failures = 0;
+                        let gzip = app.state::<AppSettings>().0.read().ws_binary_gzip;
+                        match decode_binary_frame(&data, gzip) {
+                            Ok(text) => handle_text_frame(app, &text, keys),
+                            Err(e) => log(LogLevel::Warn, &format!("start_websocket: dropped binary frame ({} bytes): {}", data.len(), e)),
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        log(LogLevel::Warn, "start_websocket: connection closed");
+                        return SessionEnd::Disconnected;
+                    }
+                    // tungstenite 已自动排队 Pong，这里立即 flush 发出去
+                    Some(Ok(Message::Ping(_))) => {
+                        log(LogLevel::Debug, "start_websocket: ping from server");
+                        if let Err(e) = ws_stream.flush().await {
+                            log(LogLevel::Error, &format!("start_websocket: pong error: {}", e));
+                            return SessionEnd::Disconnected;
+                        }
+                    }
+                    // 我们发的 Ping 载荷是发送时间（毫秒），据此计算往返延迟
+                    Some(Ok(Message::Pong(payload))) => {
+                        if let Ok(bytes) = <[u8; 8]>::try_from(payload.as_slice()) {
+                            let sent_at = u64::from_be_bytes(bytes);
+                            let latency = tick::now_millis().saturating_sub(sent_at);
+                            log(LogLevel::Debug, &format!("start_websocket: pong latency {}ms", latency));
+                            stats::on_pong(app, latency);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        log(LogLevel::Error, &format!("start_websocket: error: {}", e));
+                        return SessionEnd::Disconnected;
+                    }
+                    _ => {}
+                }
+            },
+            _ = ping_ticker.tick() => {
+                let payload = tick::now_millis().to_be_bytes().to_vec();
+                if let Err(e) = ws_stream.send(Message::Ping(payload)).await {
+                    log(LogLevel::Error, &format!("start_websocket: ping error: {}", e));
+                    return SessionEnd::Disconnected;
+                }
+            }
+            _ = tokio::time::sleep_until(last_seen + heartbeat_timeout) => {
+                log(LogLevel::Warn, &format!(
+                    "start_websocket: no frame received for {}s, dropping connection",
+                    stale_secs
+                ));
+                return SessionEnd::Disconnected;
+            }
+            command = commands.recv() => match command {
+                Some(WsCommand::Reconnect) => {
+                    log(LogLevel::Info, "start_websocket: reconnect requested");
+                    *failures = 0;
+                    let _ = ws_stream.close(None).await;
+                    return SessionEnd::Reconnect;
+                }
+                Some(WsCommand::Subscribe(added)) => {
+                    let added: Vec<String> = added.into_iter().filter(|k| !keys.contains(k)).collect();
+                    if added.is_empty() {
+                        continue;
+                    }
+                    keys.extend(added.iter().cloned());
+                    let msg = match subscribe_message(app, SubscribeAction::Subscribe, &added) {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            log(LogLevel::Error, &format!("start_websocket: build subscribe failed: {}", e));
+                            continue;
+                        }
+                    };
+                    let sent = ws_stream.send(Message::Text(msg)).await;
+                    if let Err(e) = sent {
+                        log(LogLevel::Error, &format!("start_websocket: subscribe error: {}", e));
+                        return SessionEnd::Disconnected;
+                    }
+                    log(LogLevel::Info, &format!("start_websocket: subscribed {:?}", added));
+                }
+                Some(WsCommand::Unsubscribe(removed)) => {
+                    keys.retain(|k| !removed.contains(k));
+                    let msg = match subscribe_message(app, SubscribeAction::Unsubscribe, &removed) {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            log(LogLevel::Error, &format!("start_websocket: build unsubscribe failed: {}", e));
+                            continue;
+                        }
+                    };
+                    if let Err(e) = ws_stream.send(Message::Text(msg)).await {
+                        log(LogLevel::Error, &format!("start_websocket: unsubscribe error: {}", e));
+                        return SessionEnd::Disconnected;
+                    }
+                    log(LogLevel::Info, &format!("start_websocket: unsubscribed {:?}", removed));
+                }
+                Some(WsCommand::Send(text, reply)) => {
+                    log(LogLevel::Info, &format!("start_websocket: sending custom frame: {}", logging::truncate_utf8(&text, 100)));
+                    if let Err(e) = ws_stream.send(Message::Text(text)).await {
+                        log(LogLevel::Error, &format!("start_websocket: send error: {}", e));
+                        let _ = reply.send(Err(e.into()));
+                        return SessionEnd::Disconnected;
+                    }
+                    let _ = reply.send(Ok(()));
+                }
+                None => return SessionEnd::Stop,
+            },
+            _ = shutdown.changed() => return SessionEnd::Stop,
+        }
+    }
+}
+
+fn current_insecure_tls<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<AppSettings>().0.read().insecure_tls
+}
+
+// 连接参数取自重连配置：WebSocket 消息大小限制和是否关闭 Nagle 算法
+fn current_ws_config<R: Runtime>(app: &AppHandle<R>) -> (WebSocketConfig, bool) {
+    let state = app.state::<AppSettings>();
+    let config = &state.0.read().reconnect;
+    (config.ws_config(), config.disable_nagle)
+}
+
+// 读取当前代理配置，格式不正确时返回 None 直连
+fn current_proxy<R: Runtime>(app: &AppHandle<R>) -> Option<url::Url> {
+    let proxy_url = app.state::<AppSettings>().0.read().proxy_url.clone();
+    proxy::parse_proxy(proxy_url.as_deref())
+}
+
+fn current_ws_url<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.state::<AppSettings>().0.read().ws_url.clone()
+}
+
+// 握手时提供 permessage-deflate；tungstenite 不支持该扩展，由 DeflateStream 在 TLS 之上解压，
+// 服务端不接受时按未压缩帧透传
+async fn connect_async_with_config(
+    url: &str,
+    config: Option<WebSocketConfig>,
+    disable_nagle: bool,
+    connector: Option<tokio_tungstenite::Connector>,
+    proxy: Option<&url::Url>,
+) -> Result<(WsStream, tokio_tungstenite::tungstenite::handshake::client::Response), tokio_tungstenite::tungstenite::Error> {
+    use tokio_tungstenite::tungstenite::{error::UrlError, Error};
+    
+    // 地址不合法时返回错误而不是 panic，由重连循环记录日志、推送 ws-status 后重试
+    let url = url::Url::parse(url)
+        .map_err(|e| Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("invalid URL '{url}': {e}"))))?;
+    let host = url.host_str().filter(|host| !host.is_empty()).ok_or(Error::Url(UrlError::NoHostName))?;
+    let secure = match url.scheme() {
+        "wss" => true,
+        "ws" => false,
+        _ => return Err(Error::Url(UrlError::UnsupportedUrlScheme)),
+    };
+    let request = tokio_tungstenite::tungstenite::handshake::client::Request::builder()
+        .uri(url.as_str())
+        .header("Host", host)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
+        .header("Sec-WebSocket-Extensions", deflate::EXTENSION_OFFER)
+        .body(())?;
+    
+    // 直连或经代理建立 TCP 连接（代理时为隧道），再在其上完成 TLS 和 WebSocket 握手
+    let port = url.port_or_known_default().unwrap_or(443);
+    let stream = match proxy {
+        Some(proxy) => proxy::connect_via_proxy(proxy, host, port).await?,
+        None => tokio::net::TcpStream::connect((host, port)).await?,
+    };
+    if disable_nagle {
+        if let Err(e) = stream.set_nodelay(true) {
+            log(LogLevel::Warn, &format!("connect: set TCP_NODELAY failed: {}", e));
+        }
+    }
+    // 自己完成 TLS 握手，让 DeflateStream 位于 TLS 之上、看到的是明文帧
+    let tls_connector = match connector {
+        _ if !secure => None,
+        Some(tokio_tungstenite::Connector::Plain) => None,
+        Some(tokio_tungstenite::Connector::NativeTls(connector)) => Some(connector),
+        Some(_) => return Err(Error::Url(UrlError::TlsFeatureNotEnabled)),
+        None => Some(native_tls::TlsConnector::new().map_err(|e| Error::Tls(e.into()))?),
+    };
+    let stream = match tls_connector {
+        Some(connector) => {
+            let tls = tokio_native_tls::TlsConnector::from(connector)
+                .connect(host, stream)
+                .await
+                .map_err(|e| Error::Tls(e.into()))?;
+            tokio_tungstenite::MaybeTlsStream::NativeTls(tls)
+        }
+        None => tokio_tungstenite::MaybeTlsStream::Plain(stream),
+    };
+    let max_message_size = config.unwrap_or_default().max_message_size.unwrap_or(usize::MAX);
+    tokio_tungstenite::client_async_with_config(request, deflate::DeflateStream::new(stream, max_message_size), config).await
+}
+
+// 应用窗口透明度，平台不支持时只记录警告
+fn apply_window_opacity<R: Runtime>(app: &AppHandle<R>, opacity: f64) {
+    for window in secondary::widget_windows(app) {
+        if let Err(e) = set_native_opacity(&window, opacity) {
+            log(LogLevel::Warn, &format!("apply_window_opacity: {}", e));
+        }
+    }
+}
+
+#[cfg(windows)]
+fn set_native_opacity<R: Runtime>(window: &tauri::WebviewWindow<R>, opacity: f64) -> Result<(), String> {
+    use windows::Win32::Foundation::COLORREF;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+    
+    let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+    let alpha = (opacity * 255.0).round() as u8;
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+        SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(windows))]
+fn set_native_opacity<R: Runtime>(_window: &tauri::WebviewWindow<R>, _opacity: f64) -> Result<(), String> {
+    Err("window opacity is not supported on this platform".to_string())
+}
+
+// 按布局模式和缩放比例调整窗口大小并重新吸附；允许用户调整时不限制最大尺寸，并恢复用户保存的尺寸
+fn apply_window_scale<R: Runtime>(app: &AppHandle<R>, layout_mode: &str, scale: f64) {
+    if let Some(window) = app.get_webview_window("main") {
+        let (user_resizable, saved_size) = {
+            let state = app.state::<AppSettings>();
+            let guard = state.0.read();
+            (guard.user_resizable, guard.window_w.zip(guard.window_h))
+        };
+        let (width, height) = layout_size(layout_mode);
+        let preset = tauri::LogicalSize::new(width * scale, height * scale);
+        let size = match saved_size {
+            Some((w, h)) if user_resizable => tauri::LogicalSize::new(w, h),
+            _ => preset,
+        };
+        if let Err(e) = window.set_resizable(user_resizable) {
+            log(LogLevel::Warn, &format!("apply_window_scale: set resizable failed: {}", e));
+        }
+        let max_size = if user_resizable { None } else { Some(preset) };
+        if let Err(e) = window.set_max_size(max_size) {
+            log(LogLevel::Warn, &format!("apply_window_scale: set max size failed: {}", e));
+        }
+        if let Ok(mut guard) = app.state::<ProgrammaticResize>().0.lock() {
+            *guard = Some(std::time::Instant::now());
+        }
+        if let Err(e) = window.set_size(size) {
+            log(LogLevel::Error, &format!("apply_window_scale: set size failed: {}", e));
+            return;
+        }
+    }
+    place_window(app);
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
-struct Settings {
-    show_xau: bool,
-    show_ms: bool,
-    show_gh: bool,
-    show_zs: bool,
-    bg_color: String,
+// 显示/隐藏主窗口
+fn toggle_window_visibility<R: Runtime>(app: &AppHandle<R>) {
+    toggle_window(app, secondary::MAIN_LABEL);
 }
 
-struct AppSettings(Mutex<Settings>);
+// 显示/隐藏指定窗口
+fn toggle_window<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    if let Some(window) = app.get_webview_window(label) {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = window.show();
+            let _ = window.set_focus();
+            // 按设置恢复置顶状态
+            let always_on_top = app.state::<AppSettings>().0.read().always_on_top;
+            let _ = window.set_always_on_top(always_on_top);
+        }
+    }
+}
 
-const SETTINGS_KEY: &str = "settings";
-const STORE_PATH: &str = "settings.bin";
+fn apply_always_on_top<R: Runtime>(app: &AppHandle<R>, always_on_top: bool) {
+    for window in secondary::widget_windows(app) {
+        if let Err(e) = window.set_always_on_top(always_on_top) {
+            log(LogLevel::Warn, &format!("apply_always_on_top: {}", e));
+        }
+    }
+}
 
-fn default_settings() -> Settings {
-    Settings {
-        show_xau: true,
-        show_ms: true,
-        show_gh: true,
-        show_zs: true,
-        bg_color: "#2c3e50".to_string(),
+// 鼠标穿透：开启后窗口不再接收鼠标事件，点击落到下面的窗口
+fn apply_click_through<R: Runtime>(app: &AppHandle<R>, enabled: bool) {
+    for window in secondary::widget_windows(app) {
+        match window.set_ignore_cursor_events(enabled) {
+            Ok(()) => log(LogLevel::Info, &format!("apply_click_through: {}", if enabled { "on" } else { "off" })),
+            Err(e) => log(LogLevel::Warn, &format!("apply_click_through: {}", e)),
+        }
     }
 }
 
-fn log_line(message: &str) {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
-    let mut path = std::env::temp_dir();
-    path.push("GoldPrice.log");
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
-        let _ = writeln!(file, "[{}] {}", timestamp, message);
+// 切换鼠标穿透（托盘和快捷键共用）
+fn toggle_click_through<R: Runtime>(app: &AppHandle<R>) {
+    let result = update_settings(app, |current| {
+        current.click_through = !current.click_through;
+        Ok(())
+    });
+    if let Err(e) = result {
+        log(LogLevel::Error, &format!("toggle_click_through: {}", e));
     }
 }
 
-// 获取设置
-#[tauri::command]
-fn get_settings<R: Runtime>(app: AppHandle<R>, _state: State<AppSettings>) -> Settings {
-    let store = match app.store(STORE_PATH) {
-        Ok(store) => store,
-        Err(err) => {
-            log_line(&format!("get_settings: open store failed: {err}"));
-            return default_settings();
-        }
+// 按设置把窗口放到指定显示器的角落或中心，显示器序号无效时退回主显示器
+fn position_window_anchored<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
     };
-    match store.get(SETTINGS_KEY) {
-        Some(value) => match serde_json::from_value(value) {
-            Ok(settings) => settings,
-            Err(err) => {
-                log_line(&format!("get_settings: decode failed: {err}"));
-                default_settings()
+    let (corner, monitor_index, snap_margin) = {
+        let state = app.state::<AppSettings>();
+        let guard = state.0.read();
+        (guard.anchor_corner.clone(), guard.monitor_index, guard.snap_margin)
+    };
+    let monitor = match window.available_monitors() {
+        Ok(monitors) if monitor_index < monitors.len() => Some(monitors[monitor_index].clone()),
+        _ => {
+            if monitor_index != 0 {
+                log(LogLevel::Warn, &format!(
+                    "position_window_anchored: monitor {} not found, using primary",
+                    monitor_index
+                ));
             }
-        },
-        None => default_settings(),
+            window.primary_monitor().ok().flatten()
+        }
+    };
+    let Some(monitor) = monitor else {
+        return;
+    };
+    
+    let monitor_size = monitor.size();
+    let monitor_position = monitor.position();
+    let window_size = window
+        .outer_size()
+        .unwrap_or(tauri::PhysicalSize::new(280, 40));
+    let margin = (snap_margin * monitor.scale_factor()).round() as i32;
+    let free_width = monitor_size.width as i32 - window_size.width as i32;
+    let free_height = monitor_size.height as i32 - window_size.height as i32;
+    
+    let (dx, dy) = match corner.as_str() {
+        "TopLeft" => (margin, margin),
+        "TopRight" => (free_width - margin, margin),
+        "BottomLeft" => (margin, free_height - margin),
+        "BottomRight" => (free_width - margin, free_height - margin),
+        _ => (free_width / 2, free_height / 2),
+    };
+    move_window(&window, monitor_position.x + dx, monitor_position.y + dy);
+}
+
+// 移动窗口并标记为程序行为，避免被当成手动位置保存
+fn move_window<R: Runtime>(window: &tauri::WebviewWindow<R>, x: i32, y: i32) {
+    if let Ok(mut guard) = window.app_handle().state::<ProgrammaticMove>().0.lock() {
+        *guard = Some((x, y));
     }
+    let _ = window.set_position(tauri::Position::Physical(
+        tauri::PhysicalPosition::new(x, y),
+    ));
 }
 
-// 保存设置
-#[tauri::command]
-fn save_settings<R: Runtime>(
-    app: AppHandle<R>,
-    state: State<AppSettings>,
-    settings: Settings,
-) -> Result<(), String> {
-    let store = app.store(STORE_PATH).map_err(|e| e.to_string())?;
-    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
-    store.set(SETTINGS_KEY, value);
-    store.save().map_err(|e| e.to_string())?;
-    
-    match state.0.lock() {
-        Ok(mut guard) => *guard = settings.clone(),
-        Err(_) => {
-            log_line("save_settings: settings lock poisoned");
-            return Err("Settings lock poisoned".to_string());
+// 检查窗口顶部是否落在某个显示器内，避免恢复到屏幕外
+fn position_on_screen<R: Runtime>(window: &tauri::WebviewWindow<R>, x: i32, y: i32) -> bool {
+    let Ok(monitors) = window.available_monitors() else {
+        return false;
+    };
+    let width = window
+        .outer_size()
+        .map(|size| size.width as i32)
+        .unwrap_or(BASE_WINDOW_WIDTH as i32);
+    monitors.iter().any(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        x + width > position.x
+            && x < position.x + size.width as i32
+            && y >= position.y
+            && y < position.y + size.height as i32
+    })
+}
+
+// 有保存的手动位置且在屏幕内时恢复，否则居中
+fn place_window<R: Runtime>(app: &AppHandle<R>) {
+    let saved = {
+        let state = app.state::<AppSettings>();
+        let guard = state.0.read();
+        guard.window_x.zip(guard.window_y)
+    };
+    if let (Some((x, y)), Some(window)) = (saved, app.get_webview_window("main")) {
+        if position_on_screen(&window, x, y) {
+            move_window(&window, x, y);
+            return;
         }
+        log(LogLevel::Warn, &format!("place_window: saved position ({x}, {y}) is off-screen"));
     }
-    
-    // 通知前端设置已更新
-    let _ = app.emit("settings-updated", settings);
-    Ok(())
+    position_window_anchored(app);
 }
 
-// 切换特定设置的显示状态
-#[tauri::command]
-fn toggle_platform<R: Runtime>(
-    app: AppHandle<R>,
-    state: State<AppSettings>,
-    platform: String,
-) -> Result<Settings, String> {
-    let mut current = match state.0.lock() {
-        Ok(guard) => guard.clone(),
-        Err(_) => {
-            log_line("toggle_platform: settings lock poisoned");
-            return Err("Settings lock poisoned".to_string());
+// 用户拖动窗口后记录位置（只写入存储，由插件自动保存）
+fn remember_window_position<R: Runtime>(window: &tauri::WebviewWindow<R>, x: i32, y: i32) {
+    let app = window.app_handle();
+    if let Ok(mut guard) = app.state::<ProgrammaticMove>().0.lock() {
+        if guard.take() == Some((x, y)) {
+            return;
         }
+    }
+    if !position_on_screen(window, x, y) {
+        return;
+    }
+    let writer = app.state::<SettingsWriter>();
+    let _writing = writer.0.lock();
+    let settings = {
+        let state = app.state::<AppSettings>();
+        let mut guard = state.0.write();
+        guard.window_x = Some(x);
+        guard.window_y = Some(y);
+        guard.clone()
     };
-    
-    match platform.as_str() {
-        "xau" => current.show_xau = !current.show_xau,
-        "ms" => current.show_ms = !current.show_ms,
-        "gh" => current.show_gh = !current.show_gh,
-        "zs" => current.show_zs = !current.show_zs,
-        _ => return Err("Unknown platform".to_string()),
+    match (persist::store(app), serde_json::to_value(&settings)) {
+        (Ok(store), Ok(value)) => {
+            store.set(SETTINGS_KEY, value);
+            persist::schedule_store_save(app);
+        }
+        (Err(err), _) => log(LogLevel::Error, &format!("remember_window_position: open store failed: {err}")),
+        (_, Err(err)) => log(LogLevel::Error, &format!("remember_window_position: encode failed: {err}")),
     }
-    
-    save_settings(app.clone(), state, current.clone())?;
-    Ok(current)
 }
 
-// 设置背景颜色
-#[tauri::command]
-fn set_bg_color<R: Runtime>(
-    app: AppHandle<R>,
-    state: State<AppSettings>,
-    color: String,
-) -> Result<Settings, String> {
-    let mut current = match state.0.lock() {
-        Ok(guard) => guard.clone(),
-        Err(_) => {
-            log_line("set_bg_color: settings lock poisoned");
-            return Err("Settings lock poisoned".to_string());
+// 允许用户调整大小时记住拖出的尺寸（逻辑像素），下次启动恢复
+fn remember_window_size<R: Runtime>(window: &tauri::WebviewWindow<R>, size: tauri::PhysicalSize<u32>) {
+    let app = window.app_handle();
+    if let Ok(guard) = app.state::<ProgrammaticResize>().0.lock() {
+        let programmatic = guard.is_some_and(|at| at.elapsed().as_millis() < PROGRAMMATIC_RESIZE_GRACE_MS as u128);
+        if programmatic {
+            return;
+        }
+    }
+    if size.width == 0 || size.height == 0 {
+        return;
+    }
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    let logical = size.to_logical::<f64>(scale_factor);
+    let writer = app.state::<SettingsWriter>();
+    let _writing = writer.0.lock();
+    let settings = {
+        let state = app.state::<AppSettings>();
+        let mut guard = state.0.write();
+        if !guard.user_resizable {
+            return;
         }
+        guard.window_w = Some(logical.width);
+        guard.window_h = Some(logical.height);
+        guard.clone()
     };
-    current.bg_color = color;
-    save_settings(app.clone(), state, current.clone())?;
-    Ok(current)
+    match (persist::store(app), serde_json::to_value(&settings)) {
+        (Ok(store), Ok(value)) => {
+            store.set(SETTINGS_KEY, value);
+            persist::schedule_store_save(app);
+        }
+        (Err(err), _) => log(LogLevel::Error, &format!("remember_window_size: open store failed: {err}")),
+        (_, Err(err)) => log(LogLevel::Error, &format!("remember_window_size: encode failed: {err}")),
+    }
 }
 
-// 退出应用
-#[tauri::command]
-fn quit_app<R: Runtime>(app: AppHandle<R>) {
-    app.exit(0);
+// 创建托盘菜单
+// 托盘图标句柄、上次刷新提示文字的时间（用于限流）以及涨跌指示状态
+struct TrayHandle<R: Runtime>(Mutex<TrayStatus<R>>);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PriceDirection {
+    Up,
+    Down,
 }
 
-// 自定义 HTTP 请求（跳过 SSL 验证）
-#[tauri::command]
-async fn fetch_with_no_ssl(url: String, method: String, body: Option<String>) -> Result<String, String> {
-    log_line(&format!("fetch_with_no_ssl: {} {}", method, url));
-    
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .danger_accept_invalid_hostnames(true)
-        .build()
-        .map_err(|e| {
-            log_line(&format!("fetch_with_no_ssl: client build error: {}", e));
-            e.to_string()
-        })?;
-    
-    let request = match method.to_uppercase().as_str() {
-        "GET" => client.get(&url),
-        "POST" => {
-            let mut req = client.post(&url);
-            if let Some(body_str) = body {
-                req = req.header("Content-Type", "application/json").body(body_str);
-            }
-            req
-        },
-        _ => return Err("Unsupported method".to_string()),
-    };
-    
-    let response = request.send().await.map_err(|e| {
-        log_line(&format!("fetch_with_no_ssl: request error: {}", e));
-        e.to_string()
-    })?;
-    
-    let status = response.status();
-    log_line(&format!("fetch_with_no_ssl: status {}", status));
-    
-    let text = response.text().await.map_err(|e| {
-        log_line(&format!("fetch_with_no_ssl: read body error: {}", e));
-        e.to_string()
-    })?;
-    
-    log_line(&format!("fetch_with_no_ssl: response body: {}", &text[..text.len().min(200)]));
-    
-    Ok(text)
+struct TrayStatus<R: Runtime> {
+    icon: Option<TrayIcon<R>>,
+    last_tooltip_update: Option<std::time::Instant>,
+    previous_bids: HashMap<String, f64>,
+    direction: Option<PriceDirection>,
 }
 
-// 启动 WebSocket 客户端（在 Rust 后端）
-#[tauri::command]
-async fn start_websocket<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
-    log_line("start_websocket: begin");
+impl<R: Runtime> TrayHandle<R> {
+    fn new() -> Self {
+        TrayHandle(Mutex::new(TrayStatus {
+            icon: None,
+            last_tooltip_update: None,
+            previous_bids: HashMap::new(),
+            direction: None,
+        }))
+    }
+}
+
+// 根据最新行情刷新托盘提示文字和涨跌图标
+fn update_tray<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    let (tooltip, indicator_enabled) = {
+        let state = app.state::<AppSettings>();
+        let settings = state.0.read();
+        let tooltip = settings
+            .platforms
+            .iter()
+            .find(|platform| platform.symbol.as_deref() == Some(tick.symbol.as_str()))
+            .map(|platform| tooltip::render(app, &settings, &platform.label, tick));
+        (tooltip, settings.tray_icon_indicator)
+    };
+    let Some(tooltip) = tooltip else {
+        return;
+    };
+    let Some(handle) = app.try_state::<TrayHandle<R>>() else {
+        return;
+    };
+    let Ok(mut guard) = handle.0.lock() else {
+        return;
+    };
     
-    let app_clone = app.clone();
-    tokio::spawn(async move {
-        loop {
-            log_line("start_websocket: connecting...");
-            
-            // 使用 native-tls 连接器，跳过证书验证
-            let connector = native_tls::TlsConnector::builder()
-                .danger_accept_invalid_certs(true)
-                .build()
-                .unwrap();
-            
-            let connector = tokio_tungstenite::Connector::NativeTls(connector);
-            
-            match connect_async_with_config(
-                "wss://cfws.jdjygold.com/data",
-                None,
-                false,
-                Some(connector),
-            ).await {
-                Ok((mut ws_stream, _)) => {
-                    log_line("start_websocket: connected");
-                    
-                    // 发送订阅消息
-                    let subscribe_msg = r#"{"action":"2","bizType":"2","keys":["WG-XAUUSD"]}"#;
-                    if let Err(e) = ws_stream.send(Message::Text(subscribe_msg.to_string())).await {
-                        log_line(&format!("start_websocket: send error: {}", e));
-                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                        continue;
-                    }
-                    log_line("start_websocket: subscribed");
-                    
-                    // 接收消息
-                    while let Some(msg) = ws_stream.next().await {
-                        match msg {
-                            Ok(Message::Text(text)) => {
-                                log_line(&format!("start_websocket: received: {}", &text[..text.len().min(100)]));
-                                let _ = app_clone.emit("xau-price-update", text);
-                            }
-                            Ok(Message::Close(_)) => {
-                                log_line("start_websocket: connection closed");
-                                break;
-                            }
-                            Err(e) => {
-                                log_line(&format!("start_websocket: error: {}", e));
-                                break;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                Err(e) => {
-                    log_line(&format!("start_websocket: connect error: {}", e));
-                }
+    let previous = guard.previous_bids.insert(tick.symbol.clone(), tick.bid);
+    if indicator_enabled {
+        let direction = match previous {
+            Some(prev) if tick.bid > prev => Some(PriceDirection::Up),
+            Some(prev) if tick.bid < prev => Some(PriceDirection::Down),
+            _ => guard.direction,
+        };
+        if direction != guard.direction {
+            if let Some(tray) = guard.icon.as_ref() {
+                set_tray_direction_icon(app, tray, direction);
             }
-            
-            log_line("start_websocket: reconnecting in 5s...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            guard.direction = direction;
         }
-    });
+    }
     
-    Ok(())
+    // 提示文字每秒最多刷新一次
+    let now = std::time::Instant::now();
+    let throttle = std::time::Duration::from_millis(TRAY_TOOLTIP_INTERVAL_MS);
+    if guard.last_tooltip_update.is_some_and(|last| now.duration_since(last) < throttle) {
+        return;
+    }
+    let Some(tray) = guard.icon.as_ref() else {
+        return;
+    };
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        log(LogLevel::Warn, &format!("update_tray: set tooltip failed: {}", e));
+    }
+    guard.last_tooltip_update = Some(now);
 }
 
-async fn connect_async_with_config(
-    url: &str,
-    _config: Option<()>,
-    _disable_nagle: bool,
-    connector: Option<tokio_tungstenite::Connector>,
-) -> Result<(tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>, tokio_tungstenite::tungstenite::handshake::client::Response), tokio_tungstenite::tungstenite::Error> {
-    let url = url::Url::parse(url).unwrap();
-    let request = tokio_tungstenite::tungstenite::handshake::client::Request::builder()
-        .uri(url.as_str())
-        .header("Host", url.host_str().unwrap())
-        .header("Connection", "Upgrade")
-        .header("Upgrade", "websocket")
-        .header("Sec-WebSocket-Version", "13")
-        .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
-        .body(())
-        .unwrap();
-    
-    tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector).await
+// 关闭涨跌指示后恢复默认托盘图标
+fn clear_tray_indicator<R: Runtime>(app: &AppHandle<R>) {
+    let Some(handle) = app.try_state::<TrayHandle<R>>() else {
+        return;
+    };
+    let Ok(mut guard) = handle.0.lock() else {
+        return;
+    };
+    if guard.direction.is_none() {
+        return;
+    }
+    if let Some(tray) = guard.icon.as_ref() {
+        set_tray_direction_icon(app, tray, None);
+    }
+    guard.direction = None;
 }
 
-// 显示/隐藏窗口
-fn toggle_window_visibility<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        if window.is_visible().unwrap_or(false) {
-            let _ = window.hide();
-        } else {
-            let _ = window.show();
-            let _ = window.set_focus();
-            // 确保窗口置顶
-            let _ = window.set_always_on_top(true);
-        }
+// 切换托盘图标，平台不支持时只记录日志
+fn set_tray_direction_icon<R: Runtime>(app: &AppHandle<R>, tray: &TrayIcon<R>, direction: Option<PriceDirection>) {
+    let icon = match direction {
+        Some(PriceDirection::Up) => tauri::image::Image::from_bytes(TRAY_ICON_UP).ok(),
+        Some(PriceDirection::Down) => tauri::image::Image::from_bytes(TRAY_ICON_DOWN).ok(),
+        None => app.default_window_icon().cloned(),
+    };
+    let Some(icon) = icon else {
+        log(LogLevel::Warn, "set_tray_direction_icon: icon unavailable");
+        return;
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        log(LogLevel::Warn, &format!("set_tray_direction_icon: {}", e));
     }
 }
 
-// 设置窗口到屏幕中心
-fn position_window_center<R: Runtime>(app: &AppHandle<R>) {
-    if let Some(window) = app.get_webview_window("main") {
-        if let Ok(Some(monitor)) = window.primary_monitor() {
-            let monitor_size = monitor.size();
-            let monitor_position = monitor.position();
-            let window_size = window
-                .inner_size()
-                .unwrap_or(tauri::PhysicalSize::new(280, 40));
-
-            let x = monitor_position.x as i32
-                + ((monitor_size.width as i32 - window_size.width as i32) / 2);
-            let y = monitor_position.y as i32
-                + ((monitor_size.height as i32 - window_size.height as i32) / 2);
-
-            let _ = window.set_position(tauri::Position::Physical(
-                tauri::PhysicalPosition::new(x, y),
-            ));
+// 设置变更后重建托盘菜单，使勾选状态与设置一致
+fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>) {
+    let Some(handle) = app.try_state::<TrayHandle<R>>() else {
+        return;
+    };
+    let Ok(guard) = handle.0.lock() else {
+        return;
+    };
+    let Some(tray) = guard.icon.as_ref() else {
+        return;
+    };
+    match create_tray_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                log(LogLevel::Warn, &format!("refresh_tray_menu: set menu failed: {}", e));
+            }
         }
+        Err(e) => log(LogLevel::Error, &format!("refresh_tray_menu: create menu failed: {}", e)),
     }
 }
 
-// 创建托盘菜单
 fn create_tray_menu<R: Runtime>(app: &AppHandle<R>) -> Result<Menu<R>, tauri::Error> {
-    let show_i = MenuItem::with_id(app, "show", "显示/隐藏", true, None::<&str>)?;
-    let xau_i = MenuItem::with_id(app, "toggle_xau", "显示 XAU", true, None::<&str>)?;
-    let ms_i = MenuItem::with_id(app, "toggle_ms", "显示民生", true, None::<&str>)?;
-    let gh_i = MenuItem::with_id(app, "toggle_gh", "显示工行", true, None::<&str>)?;
-    let zs_i = MenuItem::with_id(app, "toggle_zs", "显示浙商", true, None::<&str>)?;
+    let settings = app.state::<AppSettings>().0.read().clone();
+    let tr = |key| i18n::tr(&settings.language, key);
+    
+    let show_i = MenuItem::with_id(app, "show", tr("show"), true, None::<&str>)?;
+    let show_secondary_i = MenuItem::with_id(app, "show_secondary", tr("show_secondary"), true, None::<&str>)?;
     let sep = PredefinedMenuItem::separator(app)?;
     
-    // 颜色子菜单
-    let dark_i = MenuItem::with_id(app, "color_dark", "深色", true, None::<&str>)?;
-    let blue_i = MenuItem::with_id(app, "color_blue", "蓝色", true, None::<&str>)?;
-    let black_i = MenuItem::with_id(app, "color_black", "黑色", true, None::<&str>)?;
+    // 平台显示开关（菜单 id 为 platform:<id>）
+    let platform_items = settings
+        .platforms
+        .iter()
+        .map(|platform| {
+            let label = format!("{} {}", tr("show_platform"), platform.label);
+            CheckMenuItem::with_id(app, format!("platform:{}", platform.id), label, true, platform.enabled, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    // 颜色子菜单（内置颜色和用户保存的颜色，菜单 id 为 color:<hex>）
+    let color_items = colors::all_color_presets(app)
+        .into_iter()
+        .map(|preset| {
+            let checked = settings.bg_color.eq_ignore_ascii_case(&preset.hex);
+            CheckMenuItem::with_id(app, format!("color:{}", preset.hex), preset.name, true, checked, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let color_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        color_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let color_menu = Submenu::with_items(app, tr("bg_color"), true, &color_refs)?;
+    
+    // 主题子菜单（菜单 id 为 theme:<name>）
+    let theme_items = colors::THEMES
+        .iter()
+        .map(|theme| {
+            let checked = settings.bg_color.eq_ignore_ascii_case(theme.bg_color)
+                && settings.text_color.eq_ignore_ascii_case(theme.text_color)
+                && settings.opacity == theme.opacity;
+            CheckMenuItem::with_id(app, format!("theme:{}", theme.name), theme.label, true, checked, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let theme_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        theme_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let theme_menu = Submenu::with_items(app, tr("theme"), true, &theme_refs)?;
+    
+    // 配置方案子菜单（菜单 id 为 profile:<name>），没有方案时显示一个禁用的占位项
+    let profile_list = profiles::menu_profiles(app);
+    let mut profile_items = profile_list
+        .names
+        .iter()
+        .map(|name| {
+            let checked = profile_list.active.as_deref() == Some(name.as_str());
+            CheckMenuItem::with_id(app, format!("profile:{name}"), name, true, checked, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if profile_items.is_empty() {
+        profile_items.push(CheckMenuItem::with_id(app, "profile_none", tr("profile_none"), false, false, None::<&str>)?);
+    }
+    let profile_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        profile_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let profile_menu = Submenu::with_items(app, tr("profiles"), true, &profile_refs)?;
+    
+    // 文字颜色子菜单（菜单 id 为 text_color:<hex>）
+    let text_color_items = colors::TEXT_COLOR_PRESETS
+        .iter()
+        .map(|(name, hex)| {
+            let checked = settings.text_color.eq_ignore_ascii_case(hex);
+            CheckMenuItem::with_id(app, format!("text_color:{hex}"), *name, true, checked, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let text_color_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        text_color_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let text_color_menu = Submenu::with_items(app, tr("text_color"), true, &text_color_refs)?;
+    
+    // 透明度子菜单
+    let opacity_items = OPACITY_PRESETS
+        .iter()
+        .map(|percent| {
+            MenuItem::with_id(app, format!("opacity_{percent}"), format!("{percent}%"), true, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let opacity_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        opacity_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let opacity_menu = Submenu::with_items(app, tr("opacity"), true, &opacity_refs)?;
     
     let sep2 = PredefinedMenuItem::separator(app)?;
-    let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-    
-    Menu::with_items(
-        app,
-        &[
-            &show_i,
-            &sep,
-            &xau_i,
-            &ms_i,
-            &gh_i,
-            &zs_i,
-            &sep2,
-            &dark_i,
-            &blue_i,
-            &black_i,
-            &quit_i,
-        ],
-    )
+    let reconnect_i = MenuItem::with_id(app, "reconnect", tr("reconnect"), true, None::<&str>)?;
+    let copy_price_i = MenuItem::with_id(app, "copy_price", tr("copy_price"), true, None::<&str>)?;
+    let pause_i = CheckMenuItem::with_id(app, "toggle_paused", tr("pause"), true, pause::is_paused(app), None::<&str>)?;
+    let reset_pos_i = MenuItem::with_id(app, "reset_position", tr("reset_position"), true, None::<&str>)?;
+    
+    // 吸附位置子菜单（菜单 id 为 snap:<corner> 和 snap_monitor:<index>）
+    let mut snap_items = ANCHOR_CORNERS
+        .iter()
+        .map(|corner| {
+            let checked = settings.anchor_corner == *corner;
+            CheckMenuItem::with_id(app, format!("snap:{corner}"), tr(corner), true, checked, None::<&str>)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let monitor_count = app.available_monitors().map(|monitors| monitors.len()).unwrap_or(0);
+    if monitor_count > 1 {
+        for index in 0..monitor_count {
+            let checked = settings.monitor_index == index;
+            snap_items.push(CheckMenuItem::with_id(
+                app,
+                format!("snap_monitor:{index}"),
+                format!("{} {}", tr("monitor"), index + 1),
+                true,
+                checked,
+                None::<&str>,
+            )?);
+        }
+    }
+    let snap_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> =
+        snap_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>).collect();
+    let snap_menu = Submenu::with_items(app, tr("snap"), true, &snap_refs)?;
+    let tls_i = CheckMenuItem::with_id(app, "toggle_insecure_tls", tr("insecure_tls"), true, settings.insecure_tls, None::<&str>)?;
+    let on_top_i = CheckMenuItem::with_id(app, "toggle_always_on_top", tr("always_on_top"), true, settings.always_on_top, None::<&str>)?;
+    let click_through_i = CheckMenuItem::with_id(app, "toggle_click_through", tr("click_through"), true, settings.click_through, None::<&str>)?;
+    let autostart_i = CheckMenuItem::with_id(app, "toggle_autostart", tr("autostart"), true, settings.autostart, None::<&str>)?;
+    let reset_i = MenuItem::with_id(app, "reset_settings", tr("reset_settings"), true, None::<&str>)?;
+    let notify_i = CheckMenuItem::with_id(app, "toggle_notifications", tr("notifications"), true, settings.notifications_enabled, None::<&str>)?;
+    let log_dir_i = MenuItem::with_id(app, "open_data_dir", tr("open_data_dir"), true, None::<&str>)?;
+    let sep3 = PredefinedMenuItem::separator(app)?;
+    let quit_i = MenuItem::with_id(app, "quit", tr("quit"), true, None::<&str>)?;
+    
+    let mut items: Vec<&dyn tauri::menu::IsMenuItem<R>> = vec![&show_i];
+    if settings.secondary_window.enabled {
+        items.push(&show_secondary_i);
+    }
+    items.push(&sep);
+    items.extend(platform_items.iter().map(|item| item as &dyn tauri::menu::IsMenuItem<R>));
+    items.extend([
+        &sep2 as &dyn tauri::menu::IsMenuItem<R>,
+        &profile_menu,
+        &theme_menu,
+        &color_menu,
+        &text_color_menu,
+        &opacity_menu,
+        &sep3,
+        &reconnect_i,
+        &pause_i,
+        &copy_price_i,
+        &reset_pos_i,
+        &snap_menu,
+        &on_top_i,
+        &click_through_i,
+        &notify_i,
+        &tls_i,
+        &autostart_i,
+        &reset_i,
+        &log_dir_i,
+        &quit_i,
+    ]);
+    Menu::with_items(app, &items)
 }
 
 pub fn run() {
     std::panic::set_hook(Box::new(|info| {
-        log_line(&format!("panic: {info}"));
+        log(LogLevel::Error, &format!("panic: {info}"));
     }));
-    log_line("app start");
-    tauri::Builder::default()
+    log(LogLevel::Info, &format!("app start: {}", about::summary()));
+    let app = tauri::Builder::default()
         .plugin(tauri_plugin_websocket::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(persist::init())
         .plugin(tauri_plugin_store::Builder::new().build())
-        .manage(AppSettings(Mutex::new(default_settings())))
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, None))
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(hotkey::handle_shortcut)
+                .build(),
+        )
+        .manage(AppSettings(RwLock::new(default_settings())))
+        .manage(SettingsWriter(parking_lot::Mutex::new(())))
+        .manage(StoreFlush::default())
+        .manage(HealthServer::default())
+        .manage(DailyStatsState::default())
+        .manage(EmaState::default())
+        .manage(PauseState::default())
+        .manage(HoverReveal::default())
+        .manage(ProgrammaticMove(Mutex::new(None)))
+        .manage(ProgrammaticResize(Mutex::new(None)))
+        .manage(WsControl(Mutex::new(None)))
+        .manage(AlertsState::new())
+        .manage(VelocityAlertsState::default())
+        .manage(ColorPresets::new())
+        .manage(TickHistory::default())
+        .manage(RawMessages::default())
+        .manage(TickDedup::default())
+        .manage(EmitThrottle::default())
+        .manage(FeedWatch::default())
+        .manage(WsStats::default())
+        .manage(HistoryDb::default())
+        .manage(FxRefresh::default())
+        .manage(TrayHandle::<tauri::Wry>::new())
         .invoke_handler(tauri::generate_handler![
             get_settings,
             save_settings,
             toggle_platform,
             set_bg_color,
+            set_text_color,
+            apply_theme,
+            set_notifications_enabled,
+            set_insecure_tls,
+            set_hotkey,
+            set_autostart,
+            set_always_on_top,
+            snap_window,
+            normalize_window,
+            secondary::set_secondary_window,
+            set_usd_cny_rate,
+            set_precision,
+            set_price_bounds,
+            set_reconnect_config,
+            set_layout_mode,
+            set_click_through,
+            set_click_through_hotkey,
+            set_copy_price_hotkey,
+            set_tooltip_template,
+            symbols::list_known_symbols,
+            symbols::select_known_symbols,
+            clipboard::copy_price,
+            list_monitors,
+            set_opacity,
+            set_scale,
+            reset_window_position,
+            reset_settings,
+            export_settings,
+            import_settings,
             quit_app,
             fetch_with_no_ssl,
-            start_websocket
+            start_websocket,
+            stop_websocket,
+            reconnect_websocket,
+            send_ws_message,
+            alerts::set_alert,
+            alerts::list_alerts,
+            alerts::clear_alert,
+            alerts::set_velocity_alert,
+            alerts::list_velocity_alerts,
+            alerts::clear_velocity_alert,
+            attention::flash_window,
+            capture::capture_widget,
+            sound::play_alert_sound,
+            pause::set_paused,
+            tick::get_recent_ticks,
+            tick::get_last_raw_message,
+            spread::get_spread,
+            stats::get_connection_stats,
+            diagnostics::test_endpoints,
+            daily::get_daily_stats,
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::load_profile,
+            profiles::delete_profile,
+            history::query_ticks,
+            history::export_ticks_csv,
+            history::get_candles,
+            history::get_alert_history,
+            colors::list_color_presets,
+            colors::add_color_preset,
+            colors::remove_color_preset,
+            colors::list_themes,
+            about::get_app_info,
+            logging::get_log_path,
+            logging::open_data_dir,
+            logging::read_recent_logs
         ])
         .setup(|app| {
-            log_line("setup: begin");
-            // 加载设置
-            let settings: Settings = match app.store(STORE_PATH) {
+            log(LogLevel::Info, "setup: begin");
+            persist::recover_corrupt_store(app.handle());
+            // 加载设置（第一次打开 store）
+            let settings: Settings = match persist::store(app.handle()) {
                 Ok(store) => match store.get(SETTINGS_KEY) {
-                    Some(value) => serde_json::from_value(value).unwrap_or_else(|_| default_settings()),
+                    Some(value) => {
+                        // 旧版本的设置升级后写回，之后不再需要迁移
+                        let stored_version = settings_version(&value);
+                        let settings = migrate_settings(value);
+                        if stored_version < SETTINGS_VERSION {
+                            if let Ok(value) = serde_json::to_value(&settings) {
+                                store.set(SETTINGS_KEY, value);
+                                persist::schedule_store_save(app.handle());
+                            }
+                            log(LogLevel::Info, &format!(
+                                "setup: upgraded settings from v{} to v{}",
+                                stored_version, SETTINGS_VERSION
+                            ));
+                        }
+                        settings
+                    }
                     None => default_settings(),
                 },
                 Err(err) => {
-                    log_line(&format!("setup: open store failed: {err}"));
+                    log(LogLevel::Error, &format!("setup: open store failed: {err}"));
                     default_settings()
                 }
             };
             
+            logging::set_log_max_bytes(settings.log_max_bytes);
+            logging::set_log_level(settings.log_level);
+            
             // 保存到状态
-            match app.state::<AppSettings>().0.lock() {
-                Ok(mut guard) => guard.clone_from(&settings),
-                Err(_) => {
-                    log_line("setup: settings lock poisoned");
-                    return Err("settings lock poisoned".into());
-                }
+            app.state::<AppSettings>().0.write().clone_from(&settings);
+            
+            reconcile_autostart(app.handle());
+            hotkey::register_saved_hotkey(app.handle(), settings.toggle_hotkey.as_deref());
+            hotkey::register_saved_hotkey(app.handle(), settings.click_through_hotkey.as_deref());
+            hotkey::register_saved_hotkey(app.handle(), settings.copy_price_hotkey.as_deref());
+            alerts::load_alerts(app.handle());
+            history::check_integrity(app.handle());
+            history::prune_alert_history(app.handle());
+            colors::load_color_presets(app.handle());
+            tick::load_last_ticks(app.handle());
+            daily::load_daily_stats(app.handle());
+            staleness::spawn_staleness_monitor(app.handle().clone());
+            staleness::spawn_freshness_ticker(app.handle().clone());
+            hover::spawn_hover_watcher(app.handle().clone());
+            currency::spawn_fx_poller(app.handle().clone());
+            bank::spawn_bank_poller(app.handle().clone());
+            health::apply_health_port(app.handle(), settings.health_port);
+            
+            // 窗口始终先隐藏创建好，首次显示无需等待；start_hidden 时由托盘或快捷键唤出
+            if settings.start_hidden {
+                log(LogLevel::Info, "setup: starting hidden in tray");
             }
             
             // 如果配置未创建窗口，则补建一个，避免重复创建导致闪退
             if app.get_webview_window("main").is_none() {
                 let _window = WebviewWindowBuilder::new(app, "main", WebviewUrl::App("index.html".into()))
                     .title("GoldPrice")
-                    .inner_size(280.0, 40.0)
+                    .inner_size(BASE_WINDOW_WIDTH, BASE_WINDOW_HEIGHT)
                     .min_inner_size(100.0, 30.0)
-                    .always_on_top(true)
+                    .always_on_top(settings.always_on_top)
                     .decorations(false)
                     .skip_taskbar(true)
-                    .resizable(true)
+                    .resizable(settings.user_resizable)
                     .visible(false)
                     .build()?;
             }
             
-            // 设置窗口到屏幕中心
-            position_window_center(app.handle());
+            // 按布局、缩放和保存的尺寸调整窗口，再恢复手动位置，没有则吸附
+            apply_window_scale(app.handle(), &settings.layout_mode, settings.scale);
+            
+            // 记录用户拖动后的位置
+            if let Some(window) = app.get_webview_window("main") {
+                let moved_window = window.clone();
+                window.on_window_event(move |event| {
+                    match event {
+                        tauri::WindowEvent::Moved(position) => {
+                            remember_window_position(&moved_window, position.x, position.y);
+                        }
+                        tauri::WindowEvent::Resized(size) => remember_window_size(&moved_window, *size),
+                        _ => {}
+                    }
+                });
+            }
+            if !settings.always_on_top {
+                apply_always_on_top(app.handle(), false);
+            }
+            if settings.click_through {
+                apply_click_through(app.handle(), true);
+            }
+            if settings.opacity < MAX_OPACITY {
+                apply_window_opacity(app.handle(), settings.opacity);
+            }
+            
+            if let Some(window) = app.get_webview_window("main") {
+                power::watch_power_events(&window);
+            }
+            if settings.secondary_window.enabled {
+                secondary::apply(app.handle(), true);
+            }
             
             // 创建托盘（失败不影响主程序启动）
             if let Ok(tray_menu) = create_tray_menu(app.handle()) {
                 if let Some(icon) = app.default_window_icon() {
-                    let _ = TrayIconBuilder::new()
+                    let tray = TrayIconBuilder::new()
                         .icon(icon.clone())
                         .menu(&tray_menu)
-                        .tooltip("黄金价格监控")
+                        .tooltip(TRAY_TOOLTIP)
                         .on_menu_event(|app, event| match event.id.as_ref() {
                             "show" => toggle_window_visibility(app),
-                            "toggle_xau" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = toggle_platform(app.clone(), state, "xau".to_string());
+                            "show_secondary" => toggle_window(app, secondary::SECONDARY_LABEL),
+                            id if id.starts_with("platform:") => {
+                                let _ = toggle_platform(app.clone(), id["platform:".len()..].to_string());
                             }
-                            "toggle_ms" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = toggle_platform(app.clone(), state, "ms".to_string());
+                            id if id.starts_with("snap:") => {
+                                let _ = snap_window(app.clone(), Some(id["snap:".len()..].to_string()), None);
                             }
-                            "toggle_gh" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = toggle_platform(app.clone(), state, "gh".to_string());
+                            id if id.starts_with("snap_monitor:") => {
+                                if let Ok(index) = id["snap_monitor:".len()..].parse::<usize>() {
+                                    let _ = snap_window(app.clone(), None, Some(index));
+                                }
                             }
-                            "toggle_zs" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = toggle_platform(app.clone(), state, "zs".to_string());
+                            id if id.starts_with("profile:") => {
+                                let _ = profiles::load_profile(app.clone(), id["profile:".len()..].to_string());
                             }
-                            "color_dark" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = set_bg_color(app.clone(), state, "#2c3e50".to_string());
+                            id if id.starts_with("theme:") => {
+                                let _ = apply_theme(app.clone(), id["theme:".len()..].to_string());
                             }
-                            "color_blue" => {
-                                let state = app.state::<AppSettings>();
-                                let _ = set_bg_color(app.clone(), state, "#1e3a5f".to_string());
+                            id if id.starts_with("text_color:") => {
+                                let _ = set_text_color(app.clone(), id["text_color:".len()..].to_string());
+                            }
+                            id if id.starts_with("color:") => {
+                                let _ = set_bg_color(app.clone(), id["color:".len()..].to_string());
+                            }
+                            "toggle_notifications" => {
+                                let _ = update_settings(app, |current| {
+                                    current.notifications_enabled = !current.notifications_enabled;
+                                    Ok(())
+                                });
+                            }
+                            "reconnect" => {
+                                let _ = reconnect_websocket(app.clone());
+                            }
+                            "reset_position" => {
+                                let _ = reset_window_position(app.clone());
+                            }
+                            "toggle_always_on_top" => {
+                                let _ = update_settings(app, |current| {
+                                    current.always_on_top = !current.always_on_top;
+                                    Ok(())
+                                });
+                            }
+                            "toggle_click_through" => toggle_click_through(app),
+                            "toggle_paused" => pause::toggle(app),
+                            "toggle_autostart" => {
+                                let _ = update_settings(app, |current| {
+                                    current.autostart = !current.autostart;
+                                    Ok(())
+                                });
                             }
-                            "color_black" => {
+                            "toggle_insecure_tls" => {
+                                let _ = update_settings(app, |current| {
+                                    current.insecure_tls = !current.insecure_tls;
+                                    Ok(())
+                                });
+                            }
+                            "reset_settings" => {
                                 let state = app.state::<AppSettings>();
-                                let _ = set_bg_color(app.clone(), state, "#000000".to_string());
+                                let _ = reset_settings(app.clone(), state);
+                            }
+                            "copy_price" => clipboard::copy_xau_price(app),
+                            "open_data_dir" => {
+                                let _ = logging::open_data_dir(app.clone());
                             }
                             "quit" => app.exit(0),
+                            id if id.starts_with("opacity_") => {
+                                if let Ok(percent) = id["opacity_".len()..].parse::<u32>() {
+                                    let _ = set_opacity(app.clone(), percent as f64 / 100.0);
+                                }
+                            }
                             _ => {}
                         })
                         .on_tray_icon_event(|tray: &tauri::tray::TrayIcon<tauri::Wry>, event: tauri::tray::TrayIconEvent| {
@@ -467,19 +2979,224 @@ pub fn run() {
                             }
                         })
                         .build(app);
-                    log_line("setup: tray created");
+                    match tray {
+                        Ok(tray) => {
+                            if let Ok(mut guard) = app.state::<TrayHandle<tauri::Wry>>().0.lock() {
+                                guard.icon = Some(tray);
+                            }
+                            log(LogLevel::Info, "setup: tray created");
+                        }
+                        Err(err) => log(LogLevel::Error, &format!("setup: tray build failed: {err}")),
+                    }
                 } else {
-                    log_line("setup: tray icon missing");
+                    log(LogLevel::Warn, "setup: tray icon missing");
                 }
             } else {
-                log_line("setup: tray menu create failed");
+                log(LogLevel::Error, "setup: tray menu create failed");
             }
             
-            log_line("setup: done");
+            log(LogLevel::Info, "setup: done");
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .unwrap_or_else(|err| {
-            log_line(&format!("run error: {err}"));
+        .build(tauri::generate_context!());
+    // 托盘退出、关闭最后一个窗口和系统注销/关机都会走到 Exit
+    match app {
+        Ok(app) => app.run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                graceful_shutdown(app);
+            }
+        }),
+        Err(err) => log(LogLevel::Error, &format!("run error: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        const THREADS: usize = 16;
+        const UPDATES: usize = 25;
+        let writer = Arc::new(SettingsWriter(parking_lot::Mutex::new(())));
+        let settings = Arc::new(RwLock::new(default_settings()));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let writer = Arc::clone(&writer);
+                let settings = Arc::clone(&settings);
+                thread::spawn(move || {
+                    for _ in 0..UPDATES {
+                        modify_settings(
+                            &writer,
+                            &settings,
+                            |current| {
+                                current.click_through = !current.click_through;
+                                current.monitor_index += 1;
+                                Ok(())
+                            },
+                            |updated| {
+                                // 模拟 apply_settings 中较慢的校验和应用，放大竞争窗口
+                                thread::yield_now();
+                                *settings.write() = updated;
+                                Ok(())
+                            },
+                        )
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let settings = settings.read();
+        assert_eq!(settings.monitor_index, THREADS * UPDATES);
+        assert_eq!(settings.click_through, (THREADS * UPDATES) % 2 == 1);
+    }
+
+    #[test]
+    fn failed_update_leaves_settings_unchanged() {
+        let writer = SettingsWriter(parking_lot::Mutex::new(()));
+        let settings = RwLock::new(default_settings());
+        let result = modify_settings(
+            &writer,
+            &settings,
+            |current| {
+                current.monitor_index = 3;
+                Ok(())
+            },
+            |_| Err(AppError::Validation("rejected".to_string())),
+        );
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert_eq!(settings.read().monitor_index, 0);
+    }
+
+    #[test]
+    fn migrates_v0_settings() {
+        let v0 = serde_json::json!({
+            "show_xau": true,
+            "show_ms": false,
+            "show_gh": true,
+            "show_zs": false,
+            "bg_color": "#112233",
+            "opacity": 0.8,
+            "ws_url": "wss://example.com/feed",
+            "notifications_enabled": false
+        });
+        assert_eq!(settings_version(&v0), 0);
+        let settings = migrate_settings(v0);
+
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        let platforms: Vec<(&str, bool, Option<&str>, Option<u64>)> = settings
+            .platforms
+            .iter()
+            .map(|p| (p.id.as_str(), p.enabled, p.symbol.as_deref(), p.poll_interval_secs))
+            .collect();
+        let poll = Some(bank::DEFAULT_POLL_INTERVAL_SECS);
+        assert_eq!(platforms, vec![
+            ("xau", true, Some(XAU_SYMBOL), None),
+            ("ms", false, Some("JD-MS"), poll),
+            ("gh", true, Some("JD-GH"), poll),
+            ("zs", false, Some("JD-ZS"), poll),
+        ]);
+        assert_eq!(settings.bg_color, "#112233");
+        assert_eq!(settings.opacity, 0.8);
+        assert_eq!(settings.ws_url, "wss://example.com/feed");
+        assert!(!settings.notifications_enabled);
+        assert_eq!(settings.text_color, default_settings().text_color);
+    }
+
+    #[test]
+    fn migration_drops_only_invalid_fields() {
+        let stored = serde_json::json!({
+            "version": SETTINGS_VERSION,
+            "opacity": "not a number",
+            "bg_color": "#445566"
+        });
+        let settings = migrate_settings(stored);
+        assert_eq!(settings.opacity, default_settings().opacity);
+        assert_eq!(settings.bg_color, "#445566");
+    }
+
+    #[tokio::test]
+    async fn malformed_ws_url_is_an_error_not_a_panic() {
+        for url in ["not a url", "wss://", "unix:/tmp/feed.sock"] {
+            let result = connect_async_with_config(url, None, false, None, None).await;
+            assert!(result.is_err(), "{url} should fail to connect");
+        }
+    }
+
+    // 本地回显服务：把收到的请求行和请求体作为响应体返回
+    async fn spawn_echo_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    let header_end = loop {
+                        let read = socket.read(&mut buf).await.unwrap();
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..read]);
+                        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                    };
+                    let head = String::from_utf8_lossy(&request[..header_end]).into_owned();
+                    let content_length = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    while request.len() < header_end + content_length {
+                        let read = socket.read(&mut buf).await.unwrap();
+                        if read == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buf[..read]);
+                    }
+                    let request_line = head.lines().next().unwrap_or_default();
+                    let body = String::from_utf8_lossy(&request[header_end..]);
+                    let echo = format!("{request_line}\n{body}");
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{echo}",
+                        echo.len()
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
         });
+        format!("http://{address}/prices")
+    }
+
+    #[tokio::test]
+    async fn build_request_dispatches_methods() {
+        let url = spawn_echo_server().await;
+        let client = reqwest::Client::new();
+        for method in ["PUT", "patch", "Delete", "POST"] {
+            let response = build_request(&client, method, &url, Some(r#"{"a":1}"#.to_string()))
+                .unwrap()
+                .send()
+                .await
+                .unwrap();
+            let echo = response.text().await.unwrap();
+            let verb = method.to_uppercase();
+            assert_eq!(echo, format!("{verb} /prices HTTP/1.1\n{{\"a\":1}}"));
+        }
+        let response = build_request(&client, "get", &url, None).unwrap().send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "GET /prices HTTP/1.1\n");
+    }
+
+    #[test]
+    fn build_request_rejects_unknown_methods() {
+        let client = reqwest::Client::new();
+        let result = build_request(&client, "TRACE", "http://127.0.0.1/", None);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
 }
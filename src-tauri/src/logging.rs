@@ -0,0 +1,219 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::AppError;
+
+const LOG_FILE_NAME: &str = "GoldPrice.log";
+// 系统时钟异常时使用的占位时间
+const TIMESTAMP_PLACEHOLDER: &str = "0000-00-00T00:00:00.000";
+pub(crate) const DEFAULT_LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+const MIN_LOG_MAX_BYTES: u64 = 64 * 1024;
+// 保留的历史日志份数（GoldPrice.log.1、GoldPrice.log.2）
+const LOG_GENERATIONS: u32 = 2;
+const MAX_TAIL_LINES: usize = 5000;
+const TAIL_CHUNK_BYTES: u64 = 8 * 1024;
+
+// 日志级别，数值越小越重要
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::Error,
+            1 => LogLevel::Warn,
+            2 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+static LOG_MAX_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_LOG_MAX_BYTES);
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+// 写入和轮转共用一把锁，避免多个异步任务同时轮转
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+pub(crate) fn log_path() -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(LOG_FILE_NAME);
+    path
+}
+
+pub(crate) fn set_log_max_bytes(bytes: u64) {
+    LOG_MAX_BYTES.store(bytes.max(MIN_LOG_MAX_BYTES), Ordering::Relaxed);
+}
+
+pub(crate) fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+// 只写入不低于当前阈值的日志
+pub(crate) fn log(level: LogLevel, message: &str) {
+    if level > LogLevel::from_u8(LOG_LEVEL.load(Ordering::Relaxed)) {
+        return;
+    }
+    let timestamp = format_timestamp();
+    let path = log_path();
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    rotate_if_needed(&path);
+    // 整行拼好后一次写入（追加模式下不会和其他写入交错），错误日志额外刷到磁盘，崩溃前的最后几行不会丢
+    let line = format!("[{}] [{}] {}\n", timestamp, level.as_str(), message);
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        if file.write_all(line.as_bytes()).and_then(|()| file.flush()).is_ok() && level == LogLevel::Error {
+            let _ = file.sync_data();
+        }
+    }
+}
+
+// 按字节截断但不切开多字节字符，用于日志里截取报文
+pub(crate) fn truncate_utf8(text: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+// 本地时区的 ISO-8601 时间，精确到毫秒
+fn format_timestamp() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|d| DateTime::from_timestamp_millis(i64::try_from(d.as_millis()).ok()?))
+        .map(|utc| {
+            utc.with_timezone(&Local)
+                .format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+                .to_string()
+        })
+        .unwrap_or_else(|| TIMESTAMP_PLACEHOLDER.to_string())
+}
+
+// 超过大小上限时依次后移旧日志，当前日志改名为 .1
+fn rotate_if_needed(path: &Path) {
+    let max_bytes = LOG_MAX_BYTES.load(Ordering::Relaxed);
+    match fs::metadata(path) {
+        Ok(meta) if meta.len() >= max_bytes => {}
+        _ => return,
+    }
+    for generation in (1..LOG_GENERATIONS).rev() {
+        let _ = fs::rename(rotated_path(path, generation), rotated_path(path, generation + 1));
+    }
+    let _ = fs::rename(path, rotated_path(path, 1));
+}
+
+// 返回日志文件的绝对路径
+#[tauri::command]
+pub(crate) fn get_log_path() -> String {
+    log_path().to_string_lossy().into_owned()
+}
+
+// 在文件管理器中打开日志所在目录，日志文件存在时选中它
+#[tauri::command]
+pub(crate) fn open_data_dir<R: Runtime>(app: AppHandle<R>) -> Result<(), AppError> {
+    let path = log_path();
+    let opener = app.opener();
+    let result = if path.exists() {
+        opener.reveal_item_in_dir(&path)
+    } else {
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_else(std::env::temp_dir);
+        opener.open_path(dir.to_string_lossy(), None::<&str>)
+    };
+    result.map_err(|e| {
+        log(LogLevel::Error, &format!("open_data_dir: {}", e));
+        AppError::Platform(format!("Failed to open log directory: {e}"))
+    })
+}
+
+// 读取日志末尾的若干行，从文件尾部按块向前读取
+#[tauri::command]
+pub(crate) fn read_recent_logs(lines: usize) -> Result<Vec<String>, AppError> {
+    let lines = lines.min(MAX_TAIL_LINES);
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+    let path = log_path();
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut position = file.seek(SeekFrom::End(0))?;
+    let mut buffer: Vec<u8> = Vec::new();
+    // 多读一个换行，保证第一行完整
+    while position > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let chunk = TAIL_CHUNK_BYTES.min(position);
+        position -= chunk;
+        file.seek(SeekFrom::Start(position))?;
+        let mut block = vec![0u8; chunk as usize];
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&buffer);
+        buffer = block;
+    }
+    
+    let text = String::from_utf8_lossy(&buffer);
+    let all: Vec<&str> = text.lines().collect();
+    // 未读到文件开头时，第一行可能不完整，丢弃
+    let complete = if position > 0 && !all.is_empty() { &all[1..] } else { &all[..] };
+    let start = complete.len().saturating_sub(lines);
+    Ok(complete[start..].iter().map(|line| line.to_string()).collect())
+}
+
+fn rotated_path(path: &Path, generation: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{generation}"));
+    PathBuf::from(rotated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_utf8_never_splits_a_character() {
+        // 两个 ASCII 字符后接三字节汉字，第 100 个字节落在汉字中间
+        let text = format!("ab{}", "金".repeat(60));
+        assert!(!text.is_char_boundary(100));
+        let truncated = truncate_utf8(&text, 100);
+        assert!(truncated.len() <= 100);
+        assert_eq!(truncated.len(), 98);
+        assert!(text.starts_with(truncated));
+        assert!(truncated.chars().all(|c| c == 'a' || c == 'b' || c == '金'));
+    }
+
+    #[test]
+    fn truncate_utf8_keeps_short_text() {
+        assert_eq!(truncate_utf8("黄金", 100), "黄金");
+        assert_eq!(truncate_utf8("黄金", 0), "");
+        assert_eq!(truncate_utf8("黄金", 4), "黄");
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{emit_price_update, log, refresh_tray_menu, tick, update_tray, LogLevel};
+
+// 暂停显示更新：连接和接收照常（保持连接、记录历史、检查告警），只是不推送行情、不更新托盘
+#[derive(Default)]
+pub(crate) struct PauseState(AtomicBool);
+
+pub(crate) fn is_paused<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<PauseState>().0.load(Ordering::Relaxed)
+}
+
+// 切换暂停状态；恢复时立即推送各品种缓存中的最新行情
+pub(crate) fn set<R: Runtime>(app: &AppHandle<R>, paused: bool) {
+    let was_paused = app.state::<PauseState>().0.swap(paused, Ordering::Relaxed);
+    if was_paused == paused {
+        return;
+    }
+    log(LogLevel::Info, &format!("pause: updates {}", if paused { "paused" } else { "resumed" }));
+    if !paused {
+        for latest in tick::latest_ticks(app) {
+            update_tray(app, &latest);
+            emit_price_update(app, latest);
+        }
+    }
+    let _ = app.emit("paused-changed", paused);
+    refresh_tray_menu(app);
+}
+
+pub(crate) fn toggle<R: Runtime>(app: &AppHandle<R>) {
+    set(app, !is_paused(app));
+}
+
+// 暂停/恢复行情显示更新，不断开连接
+#[tauri::command]
+pub(crate) fn set_paused<R: Runtime>(app: AppHandle<R>, paused: bool) -> bool {
+    set(&app, paused);
+    paused
+}
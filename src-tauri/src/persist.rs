@@ -0,0 +1,149 @@
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tauri::{plugin::TauriPlugin, AppHandle, Emitter, Manager, RunEvent, Runtime};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::{error::AppError, log, notify, tick, LogLevel, STORE_PATH};
+
+// 最后一次修改后多久写盘
+const STORE_FLUSH_DELAY_MS: u64 = 500;
+
+// 所有读写都通过这里打开 store：关闭插件自带的自动保存（它直接覆盖写 settings.bin），
+// 写盘只走下面的 save_store；已打开时返回同一个实例
+pub(crate) fn store<R: Runtime>(app: &AppHandle<R>) -> Result<Arc<Store<R>>, tauri_plugin_store::Error> {
+    app.store_builder(STORE_PATH).disable_auto_save().build()
+}
+
+// store 插件在 Exit 时会把所有打开的 store 直接覆盖写盘；本插件需注册在它之前，
+// 先原子写入未保存的修改再关闭 store，插件就不会再写一次
+pub(crate) fn init<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("persist")
+        .on_event(|app, event| {
+            if let RunEvent::Exit = event {
+                flush_store(app);
+                if let Some(store) = app.get_store(STORE_PATH) {
+                    store.close_resource();
+                }
+            }
+        })
+        .build()
+}
+
+#[derive(Default)]
+struct FlushState {
+    generation: u64,
+    pending: bool,
+}
+
+// 设置写盘防抖：内存中的 store 立即更新，连续修改只在停止后写一次磁盘
+#[derive(Default)]
+pub(crate) struct StoreFlush(Mutex<FlushState>);
+
+// 标记 store 有未写盘的修改，延迟后若期间没有新的修改则写盘；所有修改都经这里或 mark_dirty 由 flush_store 写盘
+pub(crate) fn schedule_store_save<R: Runtime>(app: &AppHandle<R>) {
+    let generation = {
+        let flush = app.state::<StoreFlush>();
+        let Ok(mut guard) = flush.0.lock() else {
+            return;
+        };
+        guard.generation += 1;
+        guard.pending = true;
+        guard.generation
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(STORE_FLUSH_DELAY_MS)).await;
+        let latest = app
+            .state::<StoreFlush>()
+            .0
+            .lock()
+            .map(|guard| guard.generation == generation)
+            .unwrap_or(false);
+        if latest {
+            flush_store(&app);
+        }
+    });
+}
+
+// 只标记有未写盘的修改，不安排写盘（高频更新用，随下一次写盘或退出时保存）
+pub(crate) fn mark_dirty<R: Runtime>(app: &AppHandle<R>) {
+    if let Ok(mut guard) = app.state::<StoreFlush>().0.lock() {
+        guard.pending = true;
+    }
+}
+
+// 立即写入未保存的修改（退出前调用）；没有待写入内容时什么都不做
+pub(crate) fn flush_store<R: Runtime>(app: &AppHandle<R>) {
+    {
+        let flush = app.state::<StoreFlush>();
+        let Ok(mut guard) = flush.0.lock() else {
+            return;
+        };
+        if !guard.pending {
+            return;
+        }
+        guard.pending = false;
+    }
+    match save_store(app) {
+        Ok(()) => log(LogLevel::Debug, "persist: store flushed"),
+        Err(e) => log(LogLevel::Error, &format!("persist: store save failed: {}", e)),
+    }
+}
+
+// 原子写盘：先写临时文件并刷到磁盘，再改名覆盖，写到一半断电也不会留下损坏的 store
+fn save_store<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    let store = store(app)?;
+    let entries: HashMap<String, Value> = store.entries().into_iter().collect();
+    let bytes = serde_json::to_vec_pretty(&entries)?;
+    let path = tauri_plugin_store::resolve_store_path(app, STORE_PATH)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_file_name(format!("{STORE_PATH}.tmp"));
+    {
+        let mut file = File::create(&temp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, &path)?;
+    Ok(())
+}
+
+// store 文件无法解析（如写入时断电被截断）时改名备份为 settings.bin.corrupt-<时间戳>，
+// 重新创建空 store 并通知前端；返回备份文件路径，文件正常或不存在时返回 None。
+// 插件打开 store 时会忽略解析错误直接当成空 store，所以这里自己读文件检查，须在第一次打开 store 前调用
+pub(crate) fn recover_corrupt_store<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    let path = tauri_plugin_store::resolve_store_path(app, STORE_PATH).ok()?;
+    let bytes = fs::read(&path).ok()?;
+    let err = match serde_json::from_slice::<HashMap<String, Value>>(&bytes) {
+        Ok(_) => return None,
+        Err(err) => err,
+    };
+    let backup = path.with_file_name(format!("{STORE_PATH}.corrupt-{}", tick::now_millis() / 1000));
+    if let Err(e) = fs::rename(&path, &backup) {
+        log(LogLevel::Error, &format!("persist: store is corrupt ({err}) and backup failed: {e}"));
+        return None;
+    }
+    log(LogLevel::Error, &format!(
+        "persist: store is corrupt ({err}), moved to {} and reset to defaults",
+        backup.display()
+    ));
+    // 已经打开过时丢弃内存中的内容，换成空 store
+    let recreated = match app.get_store(STORE_PATH) {
+        Some(_) => app.store_builder(STORE_PATH).disable_auto_save().create_new().build(),
+        None => store(app),
+    };
+    if let Err(e) = recreated {
+        log(LogLevel::Error, &format!("persist: recreate store failed: {e}"));
+    }
+    notify(app, "黄金价格监控", "设置文件已损坏，已恢复为默认设置");
+    let _ = app.emit("settings-reset", backup.display().to_string());
+    Some(backup)
+}
@@ -0,0 +1,89 @@
+use tauri::{AppHandle, Runtime};
+
+use crate::{currency, log, send_ws_command, LogLevel, WsCommand};
+
+// 系统唤醒后立即重连 WebSocket 并刷新汇率，不等退避结束
+fn on_resume<R: Runtime>(app: &AppHandle<R>) {
+    log(LogLevel::Info, "power: system resumed, reconnecting");
+    send_ws_command(app, WsCommand::Reconnect);
+    currency::request_fx_refresh(app);
+}
+
+#[cfg(windows)]
+fn on_suspend() {
+    log(LogLevel::Info, "power: system suspending");
+}
+
+// Windows 下给主窗口挂子类过程，监听 WM_POWERBROADCAST
+#[cfg(windows)]
+pub(crate) fn watch_power_events<R: Runtime>(window: &tauri::WebviewWindow<R>) {
+    use tauri::Manager;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::{PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WM_POWERBROADCAST};
+
+    const POWER_SUBCLASS_ID: usize = 0x4a44;
+
+    type PowerCallback = Box<dyn Fn(u32) + Send + Sync>;
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        data: usize,
+    ) -> LRESULT {
+        if msg == WM_POWERBROADCAST {
+            // data 是 watch_power_events 中泄漏的回调，和窗口同生命周期
+            let callback = &*(data as *const PowerCallback);
+            callback(wparam.0 as u32);
+        }
+        DefSubclassProc(hwnd, msg, wparam, lparam)
+    }
+
+    let hwnd = match window.hwnd() {
+        Ok(hwnd) => hwnd,
+        Err(e) => {
+            log(LogLevel::Warn, &format!("power: no window handle: {}", e));
+            return;
+        }
+    };
+    let app = window.app_handle().clone();
+    let callback: Box<PowerCallback> = Box::new(Box::new(move |event| match event {
+        PBT_APMSUSPEND => on_suspend(),
+        PBT_APMRESUMEAUTOMATIC => on_resume(&app),
+        _ => {}
+    }));
+    let data = Box::into_raw(callback) as usize;
+    let installed = unsafe { SetWindowSubclass(hwnd, Some(subclass_proc), POWER_SUBCLASS_ID, data) };
+    if !installed.as_bool() {
+        log(LogLevel::Warn, "power: install window subclass failed");
+        drop(unsafe { Box::from_raw(data as *mut PowerCallback) });
+    }
+}
+
+// 其他平台没有电源广播，用墙钟跳变判断：定时器间隔远大于预期说明刚从睡眠中恢复
+#[cfg(not(windows))]
+pub(crate) fn watch_power_events<R: Runtime>(window: &tauri::WebviewWindow<R>) {
+    use std::time::{Duration, SystemTime};
+    use tauri::Manager;
+
+    const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    const RESUME_GAP: Duration = Duration::from_secs(30);
+
+    let app = window.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = SystemTime::now();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let now = SystemTime::now();
+            let elapsed = now.duration_since(last).unwrap_or_default();
+            last = now;
+            if elapsed > CHECK_INTERVAL + RESUME_GAP {
+                log(LogLevel::Info, &format!("power: clock jumped {}s, assuming suspend", elapsed.as_secs()));
+                on_resume(&app);
+            }
+        }
+    });
+}
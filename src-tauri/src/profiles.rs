@@ -0,0 +1,137 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{
+    error::AppError, log, migrate_settings, persist, place_window, refresh_tray_menu, save_settings, settings_version, AppSettings,
+    LogLevel, Settings, SETTINGS_VERSION,
+};
+
+const PROFILES_KEY: &str = "profiles";
+const ACTIVE_PROFILE_KEY: &str = "active_profile";
+
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ProfileList {
+    pub names: Vec<String>,
+    pub active: Option<String>,
+}
+
+// 读取全部配置方案（按名称排序）
+fn load_profiles<R: Runtime>(app: &AppHandle<R>) -> Result<BTreeMap<String, Settings>, AppError> {
+    let store = persist::store(app)?;
+    match store.get(PROFILES_KEY) {
+        Some(value) => {
+            let raw: BTreeMap<String, serde_json::Value> = serde_json::from_value(value)
+                .map_err(|e| AppError::Store(format!("Invalid saved profiles: {e}")))?;
+            let outdated = raw.values().any(|value| settings_version(value) < SETTINGS_VERSION);
+            let profiles = raw.into_iter().map(|(name, value)| (name, migrate_settings(value))).collect();
+            // 旧版本的方案升级后写回，避免每次读取都重新迁移
+            if outdated {
+                persist_profiles(app, &profiles, active_profile(app).as_deref())?;
+            }
+            Ok(profiles)
+        }
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn persist_profiles<R: Runtime>(
+    app: &AppHandle<R>,
+    profiles: &BTreeMap<String, Settings>,
+    active: Option<&str>,
+) -> Result<(), AppError> {
+    let store = persist::store(app)?;
+    let value = serde_json::to_value(profiles)?;
+    store.set(PROFILES_KEY, value);
+    store.set(ACTIVE_PROFILE_KEY, serde_json::json!(active));
+    persist::schedule_store_save(app);
+    Ok(())
+}
+
+pub(crate) fn active_profile<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    let store = persist::store(app).ok()?;
+    store.get(ACTIVE_PROFILE_KEY)?.as_str().map(str::to_string)
+}
+
+fn profile_list<R: Runtime>(app: &AppHandle<R>, profiles: &BTreeMap<String, Settings>) -> ProfileList {
+    ProfileList {
+        names: profiles.keys().cloned().collect(),
+        active: active_profile(app).filter(|name| profiles.contains_key(name)),
+    }
+}
+
+fn notify_profiles_changed<R: Runtime>(app: &AppHandle<R>, list: &ProfileList) {
+    refresh_tray_menu(app);
+    let _ = app.emit("profiles-updated", list.clone());
+}
+
+// 列出配置方案和当前使用的方案
+#[tauri::command]
+pub(crate) fn list_profiles<R: Runtime>(app: AppHandle<R>) -> Result<ProfileList, AppError> {
+    let profiles = load_profiles(&app)?;
+    Ok(profile_list(&app, &profiles))
+}
+
+// 把当前设置保存为配置方案（同名覆盖），并设为当前方案
+#[tauri::command]
+pub(crate) fn save_profile<R: Runtime>(app: AppHandle<R>, name: String) -> Result<ProfileList, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Profile name must not be empty".to_string()));
+    }
+    let mut profiles = load_profiles(&app)?;
+    let current = app.state::<AppSettings>().0.read().clone();
+    profiles.insert(name.clone(), current);
+    persist_profiles(&app, &profiles, Some(&name))?;
+    log(LogLevel::Info, &format!("save_profile: saved '{}'", name));
+    let list = profile_list(&app, &profiles);
+    notify_profiles_changed(&app, &list);
+    Ok(list)
+}
+
+// 加载配置方案，和 save_settings 一样应用到窗口和连接
+#[tauri::command]
+pub(crate) fn load_profile<R: Runtime>(app: AppHandle<R>, name: String) -> Result<Settings, AppError> {
+    let mut profiles = load_profiles(&app)?;
+    let settings = profiles
+        .remove(&name)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown profile '{name}'")))?;
+    save_settings(app.clone(), app.state::<AppSettings>(), settings.clone())?;
+    place_window(&app);
+    let store = persist::store(&app)?;
+    store.set(ACTIVE_PROFILE_KEY, serde_json::json!(name));
+    persist::schedule_store_save(&app);
+    log(LogLevel::Info, &format!("load_profile: loaded '{}'", name));
+    let list = list_profiles(app.clone())?;
+    notify_profiles_changed(&app, &list);
+    Ok(settings)
+}
+
+// 删除配置方案；删除的是当前方案时清空当前方案
+#[tauri::command]
+pub(crate) fn delete_profile<R: Runtime>(app: AppHandle<R>, name: String) -> Result<ProfileList, AppError> {
+    let mut profiles = load_profiles(&app)?;
+    if profiles.remove(&name).is_none() {
+        return Err(AppError::NotFound(format!("Unknown profile '{name}'")));
+    }
+    let active = active_profile(&app).filter(|active| *active != name);
+    persist_profiles(&app, &profiles, active.as_deref())?;
+    log(LogLevel::Info, &format!("delete_profile: deleted '{}'", name));
+    let list = profile_list(&app, &profiles);
+    notify_profiles_changed(&app, &list);
+    Ok(list)
+}
+
+// 托盘菜单用：方案名称列表和当前方案
+pub(crate) fn menu_profiles<R: Runtime>(app: &AppHandle<R>) -> ProfileList {
+    match load_profiles(app) {
+        Ok(profiles) => profile_list(app, &profiles),
+        Err(e) => {
+            log(LogLevel::Warn, &format!("menu_profiles: {}", e));
+            ProfileList {
+                names: Vec::new(),
+                active: None,
+            }
+        }
+    }
+}
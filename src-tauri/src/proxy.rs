@@ -0,0 +1,99 @@
+use base64::Engine;
+use std::io;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_socks::tcp::Socks5Stream;
+
+use crate::{log, LogLevel};
+
+const SUPPORTED_SCHEMES: &[&str] = &["http", "socks5", "socks5h"];
+const DEFAULT_SOCKS_PORT: u16 = 1080;
+const MAX_CONNECT_RESPONSE_BYTES: usize = 8192;
+
+// 解析代理地址，格式不正确时记录日志并返回 None，调用方直连
+pub(crate) fn parse_proxy(proxy_url: Option<&str>) -> Option<url::Url> {
+    let raw = proxy_url?.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    match url::Url::parse(raw) {
+        Ok(url) if SUPPORTED_SCHEMES.contains(&url.scheme()) && url.host_str().is_some() => Some(url),
+        Ok(url) => {
+            log(LogLevel::Warn, &format!(
+                "proxy: unsupported proxy '{}' ({}://), using direct connection",
+                raw,
+                url.scheme()
+            ));
+            None
+        }
+        Err(e) => {
+            log(LogLevel::Warn, &format!("proxy: invalid proxy '{}': {}, using direct connection", raw, e));
+            None
+        }
+    }
+}
+
+// 通过代理建立到目标主机的 TCP 隧道
+pub(crate) async fn connect_via_proxy(proxy: &url::Url, host: &str, port: u16) -> io::Result<TcpStream> {
+    let proxy_host = proxy.host_str().unwrap_or_default();
+    match proxy.scheme() {
+        "socks5" | "socks5h" => {
+            let proxy_addr = (proxy_host, proxy.port().unwrap_or(DEFAULT_SOCKS_PORT));
+            let stream = if proxy.username().is_empty() {
+                Socks5Stream::connect(proxy_addr, (host, port)).await
+            } else {
+                Socks5Stream::connect_with_password(
+                    proxy_addr,
+                    (host, port),
+                    proxy.username(),
+                    proxy.password().unwrap_or_default(),
+                )
+                .await
+            }
+            .map_err(io::Error::other)?;
+            Ok(stream.into_inner())
+        }
+        _ => http_connect(proxy, host, port).await,
+    }
+}
+
+// HTTP 代理使用 CONNECT 建立隧道
+async fn http_connect(proxy: &url::Url, host: &str, port: u16) -> io::Result<TcpStream> {
+    let proxy_host = proxy.host_str().unwrap_or_default();
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+    let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if !proxy.username().is_empty() {
+        let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or_default());
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        request.push_str(&format!("Proxy-Authorization: Basic {encoded}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // 逐字节读取响应头，避免吞掉隧道建立后的数据
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).await? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "proxy closed connection during CONNECT",
+            ));
+        }
+        response.push(byte[0]);
+        if response.len() > MAX_CONNECT_RESPONSE_BYTES {
+            return Err(io::Error::other("proxy CONNECT response too large"));
+        }
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(io::Error::other(format!("proxy CONNECT failed: {status_line}")));
+    }
+    Ok(stream)
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{
+    error::AppError, layout_size, log, move_window, persist, position_on_screen, update_settings, AppSettings,
+    LogLevel, ProgrammaticMove, Settings, SettingsWriter, SETTINGS_KEY,
+};
+
+pub(crate) const SECONDARY_LABEL: &str = "secondary";
+pub(crate) const MAIN_LABEL: &str = "main";
+// 没有保存位置时放在主窗口下方，间隔这么多物理像素
+const STACK_GAP: i32 = 4;
+
+// 副窗口：单独显示 symbols 中的品种，这些品种不再推送到主窗口
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct SecondaryWindowConfig {
+    pub enabled: bool,
+    pub symbols: Vec<String>,
+    pub window_x: Option<i32>,
+    pub window_y: Option<i32>,
+}
+
+impl Default for SecondaryWindowConfig {
+    fn default() -> Self {
+        SecondaryWindowConfig {
+            enabled: false,
+            symbols: vec!["JD-GH".to_string()],
+            window_x: None,
+            window_y: None,
+        }
+    }
+}
+
+// 某品种的行情推送到哪个窗口
+pub(crate) fn window_for_symbol(settings: &Settings, symbol: &str) -> &'static str {
+    let secondary = &settings.secondary_window;
+    if secondary.enabled && secondary.symbols.iter().any(|s| s == symbol) {
+        SECONDARY_LABEL
+    } else {
+        MAIN_LABEL
+    }
+}
+
+// 主窗口和副窗口（存在时），外观相关的设置对两个窗口同时生效
+pub(crate) fn widget_windows<R: Runtime>(app: &AppHandle<R>) -> Vec<tauri::WebviewWindow<R>> {
+    [MAIN_LABEL, SECONDARY_LABEL]
+        .iter()
+        .filter_map(|label| app.get_webview_window(label))
+        .collect()
+}
+
+// 按设置创建或关闭副窗口
+pub(crate) fn apply<R: Runtime>(app: &AppHandle<R>, enabled: bool) {
+    let existing = app.get_webview_window(SECONDARY_LABEL);
+    match (enabled, existing) {
+        (true, None) => {
+            if let Err(e) = open(app) {
+                log(LogLevel::Error, &format!("secondary: create window failed: {}", e));
+            }
+        }
+        (false, Some(window)) => {
+            log(LogLevel::Info, "secondary: closing window");
+            let _ = window.destroy();
+        }
+        _ => {}
+    }
+}
+
+// 与主窗口相同的方式创建副窗口，恢复保存的位置，没有则放在主窗口下方
+fn open<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    let settings = app.state::<AppSettings>().0.read().clone();
+    let (width, height) = layout_size(&settings.layout_mode);
+    let window = WebviewWindowBuilder::new(app, SECONDARY_LABEL, WebviewUrl::App("index.html".into()))
+        .title("GoldPrice")
+        .inner_size(width * settings.scale, height * settings.scale)
+        .min_inner_size(100.0, 30.0)
+        .always_on_top(settings.always_on_top)
+        .decorations(false)
+        .skip_taskbar(true)
+        .transparent(true)
+        .resizable(false)
+        .visible(false)
+        .build()?;
+    
+    let saved = settings
+        .secondary_window
+        .window_x
+        .zip(settings.secondary_window.window_y)
+        .filter(|(x, y)| position_on_screen(&window, *x, *y));
+    let below_main = || {
+        let main = app.get_webview_window(MAIN_LABEL)?;
+        let position = main.outer_position().ok()?;
+        let size = main.outer_size().ok()?;
+        Some((position.x, position.y + size.height as i32 + STACK_GAP))
+    };
+    if let Some((x, y)) = saved.or_else(below_main) {
+        move_window(&window, x, y);
+    }
+    if settings.click_through {
+        let _ = window.set_ignore_cursor_events(true);
+    }
+    
+    let moved_window = window.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Moved(position) = event {
+            remember_position(&moved_window, position.x, position.y);
+        }
+    });
+    log(LogLevel::Info, "secondary: window created");
+    Ok(())
+}
+
+// 用户拖动副窗口后记录位置
+fn remember_position<R: Runtime>(window: &tauri::WebviewWindow<R>, x: i32, y: i32) {
+    let app = window.app_handle();
+    if let Ok(mut guard) = app.state::<ProgrammaticMove>().0.lock() {
+        if guard.take() == Some((x, y)) {
+            return;
+        }
+    }
+    if !position_on_screen(window, x, y) {
+        return;
+    }
+    let writer = app.state::<SettingsWriter>();
+    let _writing = writer.0.lock();
+    let settings = {
+        let state = app.state::<AppSettings>();
+        let mut guard = state.0.write();
+        guard.secondary_window.window_x = Some(x);
+        guard.secondary_window.window_y = Some(y);
+        guard.clone()
+    };
+    match (persist::store(app), serde_json::to_value(&settings)) {
+        (Ok(store), Ok(value)) => {
+            store.set(SETTINGS_KEY, value);
+            persist::schedule_store_save(app);
+        }
+        (Err(err), _) => log(LogLevel::Error, &format!("secondary: open store failed: {err}")),
+        (_, Err(err)) => log(LogLevel::Error, &format!("secondary: encode failed: {err}")),
+    }
+}
+
+// 开启/关闭副窗口，symbols 为副窗口显示的品种（缺省保持不变）
+#[tauri::command]
+pub(crate) fn set_secondary_window<R: Runtime>(
+    app: AppHandle<R>,
+    enabled: bool,
+    symbols: Option<Vec<String>>,
+) -> Result<Settings, AppError> {
+    if let Some(symbols) = &symbols {
+        if symbols.iter().any(|symbol| symbol.trim().is_empty()) {
+            return Err(AppError::Validation("Secondary window symbols must not be empty".to_string()));
+        }
+    }
+    update_settings(&app, |current| {
+        current.secondary_window.enabled = enabled;
+        if let Some(symbols) = symbols {
+            current.secondary_window.symbols = symbols;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,72 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashMap, time::Duration};
+use tauri::{AppHandle, Runtime};
+use tokio::sync::{mpsc, watch};
+
+use crate::{emit_ws_status, error::AppError, handle_tick, log, tick, tick::PriceTick, LogLevel, WsCommand};
+
+pub(crate) const DEFAULT_SIMULATE_INTERVAL_MS: u64 = 1000;
+const MIN_SIMULATE_INTERVAL_MS: u64 = 50;
+// 没有缓存价格时的起始价
+const DEFAULT_START_PRICE: f64 = 2000.0;
+// 每步最大涨跌幅（相对价格）
+const STEP_PCT: f64 = 0.0005;
+const SPREAD_PCT: f64 = 0.0002;
+
+// 模拟行情任务：对每个订阅品种做随机游走，和真实行情走同一条处理路径；seed 相同时序列可复现
+pub(crate) async fn run_simulation<R: Runtime>(
+    app: AppHandle<R>,
+    mut keys: Vec<String>,
+    interval_ms: u64,
+    seed: Option<u64>,
+    mut commands: mpsc::UnboundedReceiver<WsCommand>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut prices: HashMap<String, f64> = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_millis(interval_ms.max(MIN_SIMULATE_INTERVAL_MS)));
+    log(LogLevel::Info, &format!("simulation: started for {:?}", keys));
+    emit_ws_status(&app, "connected", Some("simulation".to_string()), 0);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                for key in &keys {
+                    let price = prices.entry(key.clone()).or_insert_with(|| {
+                        tick::latest_tick(&app, key).map(|t| t.bid).unwrap_or(DEFAULT_START_PRICE)
+                    });
+                    *price *= 1.0 + rng.gen_range(-STEP_PCT..=STEP_PCT);
+                    let bid = (*price * 100.0).round() / 100.0;
+                    let ask = ((*price * (1.0 + SPREAD_PCT)) * 100.0).round() / 100.0;
+                    handle_tick(&app, PriceTick {
+                        symbol: key.clone(),
+                        bid,
+                        ask,
+                        timestamp: tick::now_millis(),
+                    });
+                }
+            }
+            command = commands.recv() => match command {
+                Some(WsCommand::Subscribe(added)) => {
+                    for key in added {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+                Some(WsCommand::Unsubscribe(removed)) => keys.retain(|k| !removed.contains(k)),
+                Some(WsCommand::Reconnect) => {}
+                Some(WsCommand::Send(_, reply)) => {
+                    let _ = reply.send(Err(AppError::NotConnected));
+                }
+                None => break,
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+    log(LogLevel::Info, "simulation: stopped");
+    emit_ws_status(&app, "disconnected", Some("stopped".to_string()), 0);
+}
@@ -0,0 +1,77 @@
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, log, AppSettings, LogLevel};
+
+pub(crate) const DEFAULT_ALERT_SOUND: &str = "chime";
+// 内置提示音，播放系统自带的声音：名称、Windows 消息提示音类型、macOS 系统声音、Linux 声音主题 id
+const SOUNDS: &[(&str, u32, &str, &str)] = &[
+    ("chime", 0, "Glass", "complete"),
+    ("alert", 48, "Sosumi", "dialog-warning"),
+    ("ding", 64, "Ping", "bell"),
+];
+
+pub(crate) fn validate_sound(name: &str) -> Result<(), AppError> {
+    if SOUNDS.iter().any(|(sound, ..)| *sound == name) {
+        Ok(())
+    } else {
+        let names: Vec<&str> = SOUNDS.iter().map(|(sound, ..)| *sound).collect();
+        Err(AppError::Validation(format!("Unknown sound '{name}': expected one of {}", names.join(", "))))
+    }
+}
+
+// 在单独线程播放，不阻塞行情处理
+pub(crate) fn play(name: &str) {
+    let Some(&(name, beep, macos, linux)) = SOUNDS.iter().find(|(sound, ..)| *sound == name) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        if let Err(e) = play_system_sound(beep, macos, linux) {
+            log(LogLevel::Warn, &format!("sound: play {name} failed: {e}"));
+        }
+    });
+}
+
+#[cfg(windows)]
+fn play_system_sound(beep: u32, _macos: &str, _linux: &str) -> Result<(), String> {
+    use windows::Win32::System::Diagnostics::Debug::MessageBeep;
+    use windows::Win32::UI::WindowsAndMessaging::MESSAGEBOX_STYLE;
+
+    unsafe { MessageBeep(MESSAGEBOX_STYLE(beep)) }.map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn play_system_sound(_beep: u32, macos: &str, _linux: &str) -> Result<(), String> {
+    let path = format!("/System/Library/Sounds/{macos}.aiff");
+    let status = std::process::Command::new("afplay").arg(path).status().map_err(|e| e.to_string())?;
+    status.success().then_some(()).ok_or_else(|| format!("afplay exited with {status}"))
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn play_system_sound(_beep: u32, _macos: &str, linux: &str) -> Result<(), String> {
+    let status = std::process::Command::new("canberra-gtk-play")
+        .args(["--id", linux])
+        .status()
+        .map_err(|e| e.to_string())?;
+    status.success().then_some(()).ok_or_else(|| format!("canberra-gtk-play exited with {status}"))
+}
+
+// 告警触发时按设置播放提示音
+pub(crate) fn on_alert<R: Runtime>(app: &AppHandle<R>) {
+    let (enabled, sound) = {
+        let state = app.state::<AppSettings>();
+        let settings = state.0.read();
+        (settings.sound_on_alert, settings.alert_sound.clone())
+    };
+    if enabled {
+        play(&sound);
+    }
+}
+
+// 试听提示音，不传名称时播放当前设置的声音
+#[tauri::command]
+pub(crate) fn play_alert_sound<R: Runtime>(app: AppHandle<R>, sound: Option<String>) -> Result<(), AppError> {
+    let sound = sound.unwrap_or_else(|| app.state::<AppSettings>().0.read().alert_sound.clone());
+    validate_sound(&sound)?;
+    play(&sound);
+    Ok(())
+}
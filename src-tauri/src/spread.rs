@@ -0,0 +1,59 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{currency, error::AppError, log, staleness, tick, AppSettings, LogLevel};
+
+// 两个品种的价差，价格按显示单位换算后再比较
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct Spread {
+    pub symbol_a: String,
+    pub symbol_b: String,
+    pub price_a: f64,
+    pub price_b: f64,
+    pub absolute: f64,
+    pub percent: f64,
+    pub timestamp: u64,
+}
+
+fn compute_spread<R: Runtime>(app: &AppHandle<R>, symbol_a: &str, symbol_b: &str) -> Result<Spread, AppError> {
+    let settings = app.state::<AppSettings>().0.read().clone();
+    let latest = |symbol: &str| -> Result<tick::PriceTick, AppError> {
+        let tick = tick::latest_tick(app, symbol).ok_or_else(|| AppError::NotFound(format!("No price for {symbol}")))?;
+        if !staleness::is_fresh(app, symbol) {
+            return Err(AppError::NotFound(format!("Price for {symbol} is stale")));
+        }
+        Ok(currency::to_display_units(&settings, &tick))
+    };
+    let a = latest(symbol_a)?;
+    let b = latest(symbol_b)?;
+    let absolute = a.bid - b.bid;
+    let percent = if b.bid != 0.0 { absolute / b.bid * 100.0 } else { 0.0 };
+    Ok(Spread {
+        symbol_a: symbol_a.to_string(),
+        symbol_b: symbol_b.to_string(),
+        price_a: a.bid,
+        price_b: b.bid,
+        absolute,
+        percent,
+        timestamp: a.timestamp.max(b.timestamp),
+    })
+}
+
+// 计算两个品种最新价的差值（a - b）和百分比
+#[tauri::command]
+pub(crate) fn get_spread<R: Runtime>(app: AppHandle<R>, symbol_a: String, symbol_b: String) -> Result<Spread, AppError> {
+    compute_spread(&app, &symbol_a, &symbol_b)
+}
+
+// 收到行情后，为设置中包含该品种的价差组合发出 spread-update 事件
+pub(crate) fn notify_tick<R: Runtime>(app: &AppHandle<R>, symbol: &str) {
+    let pairs = app.state::<AppSettings>().0.read().spread_pairs.clone();
+    for [symbol_a, symbol_b] in pairs.iter().filter(|pair| pair.iter().any(|s| s == symbol)) {
+        match compute_spread(app, symbol_a, symbol_b) {
+            Ok(spread) => {
+                let _ = app.emit("spread-update", spread);
+            }
+            Err(e) => log(LogLevel::Debug, &format!("spread: {}", e)),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::{log, AppSettings, LogLevel};
+
+pub(crate) const DEFAULT_STALENESS_THRESHOLD_SECS: u64 = 30;
+const STALENESS_CHECK_INTERVAL_SECS: u64 = 1;
+const FRESHNESS_INTERVAL_SECS: u64 = 1;
+
+struct FeedEntry {
+    last_tick: Instant,
+    stale: bool,
+}
+
+// 每个品种最近一次收到行情的时间，以及是否已经发过 feed-stale
+#[derive(Default)]
+pub(crate) struct FeedWatch(Mutex<HashMap<String, FeedEntry>>);
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct FeedStale {
+    pub symbol: String,
+    pub age_secs: u64,
+}
+
+// 各品种距上一条行情的秒数，随 freshness 事件推送
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct Freshness {
+    pub symbol: String,
+    pub age_secs: u64,
+}
+
+// 收到行情时刷新时间并清除过期标记
+pub(crate) fn mark_fresh<R: Runtime>(app: &AppHandle<R>, symbol: &str) {
+    if let Ok(mut guard) = app.state::<FeedWatch>().0.lock() {
+        guard.insert(
+            symbol.to_string(),
+            FeedEntry {
+                last_tick: Instant::now(),
+                stale: false,
+            },
+        );
+    }
+}
+
+// 后台定时检查，超过阈值没有行情的品种发出一次 feed-stale 事件
+pub(crate) fn spawn_staleness_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(STALENESS_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let threshold = app.state::<AppSettings>().0.read().staleness_threshold_secs;
+            if threshold == 0 {
+                continue;
+            }
+            for event in collect_stale(&app, Duration::from_secs(threshold)) {
+                log(LogLevel::Warn, &format!(
+                    "staleness: no tick for {} in {}s",
+                    event.symbol, event.age_secs
+                ));
+                let _ = app.emit("feed-stale", event);
+            }
+        }
+    });
+}
+
+// 独立的每秒任务：开启 show_freshness 时推送各品种的数据新鲜度，供界面显示「更新于 N 秒前」
+pub(crate) fn spawn_freshness_ticker<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(FRESHNESS_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if !app.state::<AppSettings>().0.read().show_freshness {
+                continue;
+            }
+            let ages: Vec<Freshness> = {
+                let watch = app.state::<FeedWatch>();
+                let Ok(guard) = watch.0.lock() else {
+                    continue;
+                };
+                guard
+                    .iter()
+                    .map(|(symbol, entry)| Freshness {
+                        symbol: symbol.clone(),
+                        age_secs: entry.last_tick.elapsed().as_secs(),
+                    })
+                    .collect()
+            };
+            if !ages.is_empty() {
+                let _ = app.emit("freshness", ages);
+            }
+        }
+    });
+}
+
+fn collect_stale<R: Runtime>(app: &AppHandle<R>, threshold: Duration) -> Vec<FeedStale> {
+    let watch = app.state::<FeedWatch>();
+    let Ok(mut guard) = watch.0.lock() else {
+        return Vec::new();
+    };
+    guard
+        .iter_mut()
+        .filter_map(|(symbol, entry)| {
+            let age = entry.last_tick.elapsed();
+            if entry.stale || age < threshold {
+                return None;
+            }
+            entry.stale = true;
+            Some(FeedStale {
+                symbol: symbol.clone(),
+                age_secs: age.as_secs(),
+            })
+        })
+        .collect()
+}
+
+// 是否在阈值内收到过该品种的实时行情（阈值为 0 时不判断过期）
+pub(crate) fn is_fresh<R: Runtime>(app: &AppHandle<R>, symbol: &str) -> bool {
+    let threshold = app.state::<AppSettings>().0.read().staleness_threshold_secs;
+    let watch = app.state::<FeedWatch>();
+    let Ok(guard) = watch.0.lock() else {
+        return false;
+    };
+    match guard.get(symbol) {
+        Some(_) if threshold == 0 => true,
+        Some(entry) => entry.last_tick.elapsed() < Duration::from_secs(threshold),
+        None => false,
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use std::{sync::Mutex, time::Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::AppError;
+
+// 某次连接握手后收到的字节数：wire_bytes 为线上（压缩后）帧字节，payload_bytes 为解压后的帧字节
+#[derive(Default, Clone, Copy, Debug)]
+pub(crate) struct Traffic {
+    pub compressed: bool,
+    pub wire_bytes: u64,
+    pub payload_bytes: u64,
+}
+
+#[derive(Default)]
+struct StatsInner {
+    connected_at: Option<Instant>,
+    ever_connected: bool,
+    total_reconnects: u64,
+    messages_received: u64,
+    last_tick_at: Option<Instant>,
+    latency_ms: Option<u64>,
+    out_of_order_dropped: u64,
+    // 本次连接的流量，以及之前各次连接的累计
+    traffic: Traffic,
+    earlier_wire_bytes: u64,
+    earlier_payload_bytes: u64,
+}
+
+// WebSocket 连接统计，由接收循环更新；重连时只清空本次连接的字段，累计值保留
+#[derive(Default)]
+pub(crate) struct WsStats(Mutex<StatsInner>);
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct ConnectionStats {
+    pub connected: bool,
+    pub current_uptime_secs: u64,
+    pub total_reconnects: u64,
+    pub messages_received: u64,
+    pub last_tick_age_secs: Option<u64>,
+    pub latency_ms: Option<u64>,
+    // 因时间戳早于上一条而丢弃的行情数（累计）
+    pub out_of_order_dropped: u64,
+    // 本次连接是否协商了 permessage-deflate
+    pub compression_enabled: bool,
+    // 累计收到的线上字节数和解压后字节数
+    pub wire_bytes_received: u64,
+    pub payload_bytes_received: u64,
+    // 解压后字节数 / 线上字节数，未压缩时为 1，还没有收到数据时为空
+    pub compression_ratio: Option<f64>,
+}
+
+fn update<R: Runtime>(app: &AppHandle<R>, f: impl FnOnce(&mut StatsInner)) {
+    if let Ok(mut guard) = app.state::<WsStats>().0.lock() {
+        f(&mut guard);
+    }
+}
+
+pub(crate) fn on_connected<R: Runtime>(app: &AppHandle<R>) {
+    update(app, |stats| {
+        if stats.ever_connected {
+            stats.total_reconnects += 1;
+        }
+        stats.ever_connected = true;
+        stats.connected_at = Some(Instant::now());
+        stats.last_tick_at = None;
+        stats.latency_ms = None;
+        stats.earlier_wire_bytes += stats.traffic.wire_bytes;
+        stats.earlier_payload_bytes += stats.traffic.payload_bytes;
+        stats.traffic = Traffic::default();
+    });
+}
+
+pub(crate) fn on_disconnected<R: Runtime>(app: &AppHandle<R>) {
+    update(app, |stats| stats.connected_at = None);
+}
+
+pub(crate) fn on_message<R: Runtime>(app: &AppHandle<R>, traffic: Traffic) {
+    update(app, |stats| {
+        stats.messages_received += 1;
+        stats.traffic = traffic;
+    });
+}
+
+pub(crate) fn on_tick<R: Runtime>(app: &AppHandle<R>) {
+    update(app, |stats| stats.last_tick_at = Some(Instant::now()));
+}
+
+pub(crate) fn on_out_of_order<R: Runtime>(app: &AppHandle<R>) {
+    update(app, |stats| stats.out_of_order_dropped += 1);
+}
+
+pub(crate) fn on_pong<R: Runtime>(app: &AppHandle<R>, latency_ms: u64) {
+    update(app, |stats| stats.latency_ms = Some(latency_ms));
+}
+
+// 当前连接统计的快照，供命令和健康检查接口使用
+pub(crate) fn snapshot<R: Runtime>(app: &AppHandle<R>) -> Result<ConnectionStats, AppError> {
+    let state = app.state::<WsStats>();
+    let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Stats"))?;
+    let wire_bytes = guard.earlier_wire_bytes + guard.traffic.wire_bytes;
+    let payload_bytes = guard.earlier_payload_bytes + guard.traffic.payload_bytes;
+    Ok(ConnectionStats {
+        connected: guard.connected_at.is_some(),
+        current_uptime_secs: guard.connected_at.map(|at| at.elapsed().as_secs()).unwrap_or(0),
+        total_reconnects: guard.total_reconnects,
+        messages_received: guard.messages_received,
+        last_tick_age_secs: guard.last_tick_at.map(|at| at.elapsed().as_secs()),
+        latency_ms: guard.latency_ms,
+        out_of_order_dropped: guard.out_of_order_dropped,
+        compression_enabled: guard.connected_at.is_some() && guard.traffic.compressed,
+        wire_bytes_received: wire_bytes,
+        payload_bytes_received: payload_bytes,
+        compression_ratio: (wire_bytes > 0).then(|| payload_bytes as f64 / wire_bytes as f64),
+    })
+}
+
+// 获取连接统计：是否在线、本次连接时长、累计重连次数和消息数、最近行情距今秒数、延迟、乱序丢弃数、压缩前后的字节数和压缩比
+#[tauri::command]
+pub(crate) fn get_connection_stats<R: Runtime>(app: AppHandle<R>) -> Result<ConnectionStats, AppError> {
+    snapshot(&app)
+}
@@ -0,0 +1,75 @@
+use serde::Serialize;
+use tauri::{AppHandle, Runtime};
+
+use crate::{default_platforms, error::AppError, update_settings, Settings, DEFAULT_BIZ_TYPE, XAU_SYMBOL};
+
+// 应用支持的行情：行情代码、显示名称、WebSocket 订阅用的 bizType（银行报价走 HTTP 轮询，没有 bizType）和对应平台 id
+#[derive(Serialize, Debug, Clone, Copy)]
+pub(crate) struct KnownSymbol {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub biz_type: Option<&'static str>,
+    #[serde(skip)]
+    pub platform_id: &'static str,
+}
+
+pub(crate) const KNOWN_SYMBOLS: &[KnownSymbol] = &[
+    KnownSymbol { key: XAU_SYMBOL, label: "国际金价（美元/盎司）", biz_type: Some(DEFAULT_BIZ_TYPE), platform_id: "xau" },
+    KnownSymbol { key: "JD-MS", label: "民生积存金", biz_type: None, platform_id: "ms" },
+    KnownSymbol { key: "JD-GH", label: "工行积存金", biz_type: None, platform_id: "gh" },
+    KnownSymbol { key: "JD-ZS", label: "浙商积存金", biz_type: None, platform_id: "zs" },
+];
+
+// 按显示名称或行情代码查找
+fn find(name: &str) -> Option<&'static KnownSymbol> {
+    let name = name.trim();
+    KNOWN_SYMBOLS.iter().find(|known| known.label == name || known.key == name)
+}
+
+// 列出可选的行情，供设置界面做选择列表
+#[tauri::command]
+pub(crate) fn list_known_symbols() -> Vec<KnownSymbol> {
+    KNOWN_SYMBOLS.to_vec()
+}
+
+// 按选中的名称启用对应平台（缺少的平台会补上），表中其他平台停用，自定义平台不变；
+// 选中的 WebSocket 行情必须属于同一个 bizType，并写入订阅设置
+#[tauri::command]
+pub(crate) fn select_known_symbols<R: Runtime>(
+    app: AppHandle<R>,
+    labels: Vec<String>,
+) -> Result<Settings, AppError> {
+    let selected = labels
+        .iter()
+        .map(|label| find(label).ok_or_else(|| AppError::NotFound(format!("Unknown symbol '{}'", label.trim()))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut biz_types = selected.iter().filter_map(|known| known.biz_type);
+    let biz_type = biz_types.next();
+    if let Some(other) = biz_types.find(|other| Some(*other) != biz_type) {
+        return Err(AppError::Validation(format!(
+            "Selected symbols use different biz types ({} and {other}) and cannot share one subscription",
+            biz_type.unwrap_or_default()
+        )));
+    }
+    update_settings(&app, |current| {
+        for known in KNOWN_SYMBOLS {
+            let enabled = selected.iter().any(|chosen| chosen.key == known.key);
+            match current.platforms.iter_mut().find(|platform| platform.id == known.platform_id) {
+                Some(platform) => {
+                    platform.enabled = enabled;
+                    platform.symbol = Some(known.key.to_string());
+                }
+                None if enabled => {
+                    if let Some(platform) = default_platforms().into_iter().find(|platform| platform.id == known.platform_id) {
+                        current.platforms.push(platform);
+                    }
+                }
+                None => {}
+            }
+        }
+        if let Some(biz_type) = biz_type {
+            current.biz_type = biz_type.to_string();
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{emit_price_update, error::AppError, tick::PriceTick, AppSettings};
+
+// 限流时允许的最低推送频率，0 表示不限流
+const MIN_EMIT_HZ: f64 = 0.01;
+
+#[derive(Default)]
+struct SymbolThrottle {
+    last_emit: Option<Instant>,
+    pending: Option<PriceTick>,
+    flush_scheduled: bool,
+}
+
+// 各品种上次推送时间和等待推送的最新行情
+#[derive(Default)]
+pub(crate) struct EmitThrottle(Mutex<HashMap<String, SymbolThrottle>>);
+
+pub(crate) fn validate_max_emit_hz(max_emit_hz: f64) -> Result<(), AppError> {
+    if max_emit_hz == 0.0 || (max_emit_hz.is_finite() && max_emit_hz >= MIN_EMIT_HZ) {
+        return Ok(());
+    }
+    Err(AppError::Validation(format!(
+        "Max emit rate must be 0 (unlimited) or at least {MIN_EMIT_HZ} Hz, got {max_emit_hz}"
+    )))
+}
+
+// 按 max_emit_hz 限制每个品种的推送频率，区间内只保留最新一条，到期后补发
+pub(crate) fn emit_price<R: Runtime>(app: &AppHandle<R>, tick: PriceTick) {
+    let max_emit_hz = app.state::<AppSettings>().0.read().max_emit_hz;
+    // 频率过低导致周期超出 Duration 范围时按不限流处理，不在接收循环里 panic
+    let period = if max_emit_hz > 0.0 {
+        Duration::try_from_secs_f64(1.0 / max_emit_hz).ok()
+    } else {
+        None
+    };
+
+    let throttle = app.state::<EmitThrottle>();
+    let Ok(mut guard) = throttle.0.lock() else {
+        emit_price_update(app, tick);
+        return;
+    };
+    let Some(period) = period else {
+        // 不限流时丢弃等待补发的旧行情，避免到期后盖住之后直接推送的新价格
+        guard.remove(&tick.symbol);
+        drop(guard);
+        emit_price_update(app, tick);
+        return;
+    };
+    let entry = guard.entry(tick.symbol.clone()).or_default();
+    let elapsed = entry.last_emit.map(|at| at.elapsed());
+    if elapsed.is_none_or(|elapsed| elapsed >= period) {
+        entry.last_emit = Some(Instant::now());
+        entry.pending = None;
+        drop(guard);
+        emit_price_update(app, tick);
+        return;
+    }
+
+    let symbol = tick.symbol.clone();
+    entry.pending = Some(tick);
+    if entry.flush_scheduled {
+        return;
+    }
+    entry.flush_scheduled = true;
+    let wait = period.saturating_sub(elapsed.unwrap_or_default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(wait).await;
+        let pending = {
+            let throttle = app.state::<EmitThrottle>();
+            let Ok(mut guard) = throttle.0.lock() else {
+                return;
+            };
+            let Some(entry) = guard.get_mut(&symbol) else {
+                return;
+            };
+            entry.flush_scheduled = false;
+            let pending = entry.pending.take();
+            if pending.is_some() {
+                entry.last_emit = Some(Instant::now());
+            }
+            pending
+        };
+        if let Some(tick) = pending {
+            emit_price_update(&app, tick);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_max_emit_hz() {
+        for valid in [0.0, MIN_EMIT_HZ, 2.5, 1000.0] {
+            assert!(validate_max_emit_hz(valid).is_ok(), "{valid}");
+        }
+        for invalid in [-1.0, 1e-20, 0.005, f64::NAN, f64::INFINITY] {
+            assert!(matches!(validate_max_emit_hz(invalid), Err(AppError::Validation(_))), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::{error::AppError, log, logging, persist, LogLevel};
+
+// 每个品种在内存中保留的最近行情条数
+const TICK_HISTORY_CAPACITY: usize = 200;
+const LAST_TICKS_KEY: &str = "last_ticks";
+// 最新行情写入存储的最小间隔
+const LAST_TICKS_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+const DEDUP_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+// 每条原始消息保留的最大字节数
+const RAW_MESSAGE_MAX_BYTES: usize = 4096;
+
+// 推送给前端的单条行情
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct PriceTick {
+    pub symbol: String,
+    pub bid: f64,
+    pub ask: f64,
+    // 毫秒时间戳
+    pub timestamp: u64,
+}
+
+// 某品种允许的价格范围，超出视为异常数据丢弃（两端都可不设）
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct PriceBounds {
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+}
+
+impl PriceBounds {
+    pub(crate) fn contains(&self, price: f64) -> bool {
+        self.min_price.is_none_or(|min| price >= min) && self.max_price.is_none_or(|max| price <= max)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), AppError> {
+        let finite = |bound: Option<f64>| bound.is_none_or(|value| value.is_finite());
+        if !finite(self.min_price) || !finite(self.max_price) {
+            return Err(AppError::Validation("Price bounds must be numbers".to_string()));
+        }
+        if let (Some(min), Some(max)) = (self.min_price, self.max_price) {
+            if min > max {
+                return Err(AppError::Validation(format!("Minimum price {min} is above maximum {max}")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PriceTick {
+    // 解析京东行情推送，结构为 {"data": {...}} 或直接是行情对象；
+    // 消息里没有品种代码且只订阅了一个品种时，归属到该品种
+    pub(crate) fn from_ws_json(text: &str, keys: &[String]) -> Result<PriceTick, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("invalid json: {e}"))?;
+        let quote = if value["data"].is_object() {
+            &value["data"]
+        } else {
+            &value
+        };
+        if !quote.is_object() {
+            return Err("message is not an object".to_string());
+        }
+
+        let symbol = first_str(quote, &["key", "symbol", "code"])
+            .or_else(|| first_str(&value, &["key", "symbol", "code"]))
+            .or_else(|| match keys {
+                [only] => Some(only.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| "missing symbol".to_string())?;
+
+        let last = first_f64(quote, &["lastPrice", "price"]);
+        let bid = first_f64(quote, &["bid", "buyPrice"])
+            .or(last)
+            .ok_or_else(|| "missing price".to_string())?;
+        let ask = first_f64(quote, &["ask", "sellPrice"]).or(last).unwrap_or(bid);
+
+        let timestamp = first_f64(quote, &["timestamp", "time", "ts"])
+            .or_else(|| first_f64(&value, &["timestamp", "time", "ts"]))
+            .map(|ts| normalize_millis(ts as u64))
+            .unwrap_or_else(now_millis);
+
+        Ok(PriceTick {
+            symbol,
+            bid,
+            ask,
+            timestamp,
+        })
+    }
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// 秒级时间戳统一换算成毫秒
+fn normalize_millis(ts: u64) -> u64 {
+    if ts < 10_000_000_000 {
+        ts * 1000
+    } else {
+        ts
+    }
+}
+
+fn first_str(value: &Value, fields: &[&str]) -> Option<String> {
+    fields
+        .iter()
+        .find_map(|field| value[*field].as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+// 价格字段可能是数字也可能是字符串
+fn first_f64(value: &Value, fields: &[&str]) -> Option<f64> {
+    fields.iter().find_map(|field| match &value[*field] {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+#[derive(Default)]
+struct TickHistoryInner {
+    ticks: HashMap<String, VecDeque<PriceTick>>,
+    last_flush: Option<Instant>,
+}
+
+// 各品种最近行情的环形缓冲
+#[derive(Default)]
+pub(crate) struct TickHistory(Mutex<TickHistoryInner>);
+
+// 记录一条行情，并定期把各品种最新价写入存储，冷启动时可立即显示
+pub(crate) fn record_tick<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) {
+    let history = app.state::<TickHistory>();
+    let latest = {
+        let Ok(mut guard) = history.0.lock() else {
+            return;
+        };
+        let buffer = guard.ticks.entry(tick.symbol.clone()).or_default();
+        if buffer.len() >= TICK_HISTORY_CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(tick.clone());
+
+        let due = guard
+            .last_flush
+            .is_none_or(|at| at.elapsed() >= LAST_TICKS_FLUSH_INTERVAL);
+        if !due {
+            return;
+        }
+        guard.last_flush = Some(Instant::now());
+        guard
+            .ticks
+            .values()
+            .filter_map(|buffer| buffer.back().cloned())
+            .collect::<Vec<_>>()
+    };
+
+    match persist::store(app) {
+        Ok(store) => match serde_json::to_value(&latest) {
+            Ok(value) => {
+                // 只更新内存，随下一次写盘（或退出时）一起保存
+                store.set(LAST_TICKS_KEY, value);
+                persist::mark_dirty(app);
+            }
+            Err(err) => log(LogLevel::Error, &format!("record_tick: encode failed: {err}")),
+        },
+        Err(err) => log(LogLevel::Error, &format!("record_tick: open store failed: {err}")),
+    }
+}
+
+// 启动时用上次保存的最新价填充缓冲
+pub(crate) fn load_last_ticks<R: Runtime>(app: &AppHandle<R>) {
+    let store = match persist::store(app) {
+        Ok(store) => store,
+        Err(err) => {
+            log(LogLevel::Error, &format!("load_last_ticks: open store failed: {err}"));
+            return;
+        }
+    };
+    let Some(value) = store.get(LAST_TICKS_KEY) else {
+        return;
+    };
+    let ticks: Vec<PriceTick> = match serde_json::from_value(value) {
+        Ok(ticks) => ticks,
+        Err(err) => {
+            log(LogLevel::Error, &format!("load_last_ticks: decode failed: {err}"));
+            return;
+        }
+    };
+    if let Ok(mut guard) = app.state::<TickHistory>().0.lock() {
+        for tick in ticks {
+            guard
+                .ticks
+                .entry(tick.symbol.clone())
+                .or_default()
+                .push_back(tick);
+        }
+    }
+}
+
+// 获取某品种最近的行情（按时间正序），limit 缺省返回全部缓存
+#[tauri::command]
+pub(crate) fn get_recent_ticks(
+    state: State<TickHistory>,
+    symbol: String,
+    limit: Option<usize>,
+) -> Result<Vec<PriceTick>, AppError> {
+    let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Tick history"))?;
+    let Some(buffer) = guard.ticks.get(&symbol) else {
+        return Ok(Vec::new());
+    };
+    let limit = limit.unwrap_or(TICK_HISTORY_CAPACITY).min(buffer.len());
+    Ok(buffer.iter().skip(buffer.len() - limit).cloned().collect())
+}
+
+// 某品种缓存中 since（毫秒时间戳）之后行情买价的最低和最高值
+pub(crate) fn bid_range_since<R: Runtime>(app: &AppHandle<R>, symbol: &str, since: u64) -> Option<(f64, f64)> {
+    let history = app.state::<TickHistory>();
+    let guard = history.0.lock().ok()?;
+    guard
+        .ticks
+        .get(symbol)?
+        .iter()
+        .filter(|tick| tick.timestamp >= since)
+        .fold(None, |range, tick| match range {
+            None => Some((tick.bid, tick.bid)),
+            Some((low, high)) => Some((f64::min(low, tick.bid), f64::max(high, tick.bid))),
+        })
+}
+
+// 各品种缓存中的最新行情
+pub(crate) fn latest_ticks<R: Runtime>(app: &AppHandle<R>) -> Vec<PriceTick> {
+    let history = app.state::<TickHistory>();
+    let Ok(guard) = history.0.lock() else {
+        return Vec::new();
+    };
+    guard.ticks.values().filter_map(|buffer| buffer.back().cloned()).collect()
+}
+
+// 某品种缓存中的最新行情
+pub(crate) fn latest_tick<R: Runtime>(app: &AppHandle<R>, symbol: &str) -> Option<PriceTick> {
+    let history = app.state::<TickHistory>();
+    let guard = history.0.lock().ok()?;
+    guard.ticks.get(symbol)?.back().cloned()
+}
+
+#[derive(Default)]
+struct TickDedupInner {
+    last: HashMap<String, (f64, f64)>,
+    last_timestamp: HashMap<String, u64>,
+    suppressed: u64,
+    last_report: Option<Instant>,
+}
+
+// 各品种上一次推送的买卖价和已接受的最新时间戳，用于过滤重复和乱序行情
+#[derive(Default)]
+pub(crate) struct TickDedup(Mutex<TickDedupInner>);
+
+// 时间戳早于该品种已接受的最新行情则视为乱序（网络重排或重复帧），不更新记录
+pub(crate) fn is_out_of_order<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) -> bool {
+    let dedup = app.state::<TickDedup>();
+    let Ok(mut guard) = dedup.0.lock() else {
+        return false;
+    };
+    match guard.last_timestamp.get(&tick.symbol) {
+        Some(&last) if tick.timestamp < last => {
+            log(LogLevel::Debug, &format!(
+                "tick: dropped out-of-order {} at {} (last {})",
+                tick.symbol, tick.timestamp, last
+            ));
+            true
+        }
+        _ => {
+            guard.last_timestamp.insert(tick.symbol.clone(), tick.timestamp);
+            false
+        }
+    }
+}
+
+// 买卖价与上一次推送相同则视为重复，并定期记录累计过滤条数
+pub(crate) fn is_duplicate<R: Runtime>(app: &AppHandle<R>, tick: &PriceTick) -> bool {
+    let dedup = app.state::<TickDedup>();
+    let Ok(mut guard) = dedup.0.lock() else {
+        return false;
+    };
+    let current = (tick.bid, tick.ask);
+    if guard.last.get(&tick.symbol) != Some(&current) {
+        guard.last.insert(tick.symbol.clone(), current);
+        return false;
+    }
+    guard.suppressed += 1;
+    let due = guard
+        .last_report
+        .is_none_or(|at| at.elapsed() >= DEDUP_REPORT_INTERVAL);
+    if due {
+        guard.last_report = Some(Instant::now());
+        log(LogLevel::Info, &format!("dedup: {} duplicate ticks suppressed so far", guard.suppressed));
+    }
+    true
+}
+
+#[derive(Default)]
+struct RawMessagesInner {
+    by_symbol: HashMap<String, String>,
+    latest: Option<String>,
+}
+
+// 各品种最近一条原始消息（解析前的 JSON 文本），用于对照解析结果排查问题
+#[derive(Default)]
+pub(crate) struct RawMessages(Mutex<RawMessagesInner>);
+
+// 解析前记录原始消息；解析出品种后再调用 assign_raw 归到该品种下
+pub(crate) fn record_raw<R: Runtime>(app: &AppHandle<R>, text: &str) {
+    if let Ok(mut guard) = app.state::<RawMessages>().0.lock() {
+        guard.latest = Some(logging::truncate_utf8(text, RAW_MESSAGE_MAX_BYTES).to_string());
+    }
+}
+
+pub(crate) fn assign_raw<R: Runtime>(app: &AppHandle<R>, symbol: &str) {
+    if let Ok(mut guard) = app.state::<RawMessages>().0.lock() {
+        if let Some(latest) = guard.latest.clone() {
+            guard.by_symbol.insert(symbol.to_string(), latest);
+        }
+    }
+}
+
+// 获取某品种最近一条原始消息；不传品种时返回最近收到的任意一条（包括解析失败的）
+#[tauri::command]
+pub(crate) fn get_last_raw_message(
+    state: State<RawMessages>,
+    symbol: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let guard = state.0.lock().map_err(|_| AppError::LockPoisoned("Raw messages"))?;
+    Ok(match symbol {
+        Some(symbol) => guard.by_symbol.get(&symbol).cloned(),
+        None => guard.latest.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(list: &[&str]) -> Vec<String> {
+        list.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_data_envelope() {
+        let text = r#"{"data":{"key":"WG-XAUUSD","bid":2345.6,"ask":2346.1,"timestamp":1700000000123}}"#;
+        let tick = PriceTick::from_ws_json(text, &keys(&["WG-XAUUSD", "JD-MS"])).unwrap();
+        assert_eq!(tick.symbol, "WG-XAUUSD");
+        assert_eq!(tick.bid, 2345.6);
+        assert_eq!(tick.ask, 2346.1);
+        assert_eq!(tick.timestamp, 1_700_000_000_123);
+    }
+
+    #[test]
+    fn parses_string_prices() {
+        let text = r#"{"symbol":"JD-MS","buyPrice":" 612.35 ","sellPrice":"613.05","ts":1700000000123}"#;
+        let tick = PriceTick::from_ws_json(text, &[]).unwrap();
+        assert_eq!(tick.symbol, "JD-MS");
+        assert_eq!(tick.bid, 612.35);
+        assert_eq!(tick.ask, 613.05);
+
+        // 只有成交价时买卖价都取成交价
+        let tick = PriceTick::from_ws_json(r#"{"code":"JD-GH","lastPrice":"600.5"}"#, &[]).unwrap();
+        assert_eq!((tick.bid, tick.ask), (600.5, 600.5));
+    }
+
+    #[test]
+    fn falls_back_to_single_configured_key() {
+        let text = r#"{"data":{"price":2345.6}}"#;
+        let tick = PriceTick::from_ws_json(text, &keys(&["WG-XAUUSD"])).unwrap();
+        assert_eq!(tick.symbol, "WG-XAUUSD");
+        assert_eq!(tick.bid, 2345.6);
+
+        let error = PriceTick::from_ws_json(text, &keys(&["WG-XAUUSD", "JD-MS"])).unwrap_err();
+        assert_eq!(error, "missing symbol");
+        assert!(PriceTick::from_ws_json(text, &[]).is_err());
+    }
+
+    #[test]
+    fn converts_second_timestamps_to_millis() {
+        let text = r#"{"data":{"key":"JD-ZS","bid":1.0},"time":1700000000}"#;
+        let tick = PriceTick::from_ws_json(text, &[]).unwrap();
+        assert_eq!(tick.timestamp, 1_700_000_000_000);
+        assert_eq!(normalize_millis(1_700_000_000), 1_700_000_000_000);
+        assert_eq!(normalize_millis(1_700_000_000_000), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        let error = PriceTick::from_ws_json(r#"{"data":{"key":"#, &[]).unwrap_err();
+        assert!(error.starts_with("invalid json"), "{error}");
+        assert_eq!(PriceTick::from_ws_json("[1,2]", &[]).unwrap_err(), "message is not an object");
+        assert_eq!(
+            PriceTick::from_ws_json(r#"{"key":"JD-MS","bid":"n/a"}"#, &[]).unwrap_err(),
+            "missing price"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use tauri::{AppHandle, Runtime};
+
+use crate::{currency, daily, error::AppError, tick::PriceTick, Settings};
+
+// 与原来固定的“平台名: 买价”一致
+pub(crate) const DEFAULT_TOOLTIP_TEMPLATE: &str = "{label}: {bid}";
+// 模板支持的占位符
+const PLACEHOLDERS: &[&str] = &["label", "symbol", "bid", "ask", "change", "change_pct"];
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+// 按 {name} 拆分模板；括号不成对或占位符未知时报错
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, AppError> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(AppError::Validation(format!("Unmatched '}}' in tooltip template '{template}'")));
+        }
+        let Some(len) = rest[start + 1..].find('}') else {
+            return Err(AppError::Validation(format!("Unclosed '{{' in tooltip template '{template}'")));
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(AppError::Validation(format!(
+                "Unknown placeholder '{{{name}}}' in tooltip template: expected one of {}",
+                PLACEHOLDERS.iter().map(|name| format!("{{{name}}}")).collect::<Vec<_>>().join(", ")
+            )));
+        }
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        segments.push(Segment::Placeholder(name));
+        rest = &rest[start + len + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    Ok(segments)
+}
+
+pub(crate) fn validate_template(template: &str) -> Result<(), AppError> {
+    if template.trim().is_empty() {
+        return Err(AppError::Validation("Tooltip template must not be empty".to_string()));
+    }
+    parse(template).map(|_| ())
+}
+
+// 用最新行情填充模板；价格与界面一致按显示单位和精度格式化，当天还没有统计时涨跌显示 --
+pub(crate) fn render<R: Runtime>(app: &AppHandle<R>, settings: &Settings, label: &str, tick: &PriceTick) -> String {
+    let Ok(segments) = parse(&settings.tooltip_template) else {
+        return format!("{}: {}", label, tick.bid);
+    };
+    let display = currency::format_tick(settings, tick, None);
+    let digits = display.precision as usize;
+    let stats = daily::current(app, &tick.symbol);
+    let mut text = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(part) => text.push_str(part),
+            Segment::Placeholder("label") => text.push_str(label),
+            Segment::Placeholder("symbol") => text.push_str(&tick.symbol),
+            Segment::Placeholder("bid") => text.push_str(&display.bid_text),
+            Segment::Placeholder("ask") => text.push_str(&display.ask_text),
+            Segment::Placeholder("change") => match &stats {
+                Some(stats) => {
+                    let change = currency::to_display_price(settings, &tick.symbol, stats.change);
+                    text.push_str(&format!("{change:+.digits$}"));
+                }
+                None => text.push_str("--"),
+            },
+            Segment::Placeholder("change_pct") => match &stats {
+                Some(stats) => text.push_str(&format!("{:+.2}%", stats.change_pct)),
+                None => text.push_str("--"),
+            },
+            Segment::Placeholder(_) => {}
+        }
+    }
+    text
+}